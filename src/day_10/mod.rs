@@ -1,27 +1,58 @@
-use itertools::Itertools;
+use rules::{Connectivity, Rules};
 
-pub fn get_map() -> Vec<Vec<u8>> {
-    let input = include_str!("input.txt");
+use crate::bitset::BitSet;
+
+mod render;
+mod rules;
+mod trails;
+
+/// Parses the heights, with `.` for cells that can't be walked on.
+pub fn get_map(input: &str) -> Vec<Vec<Option<u8>>> {
     input
         .lines()
         .map(|line| {
             line.chars()
-                .map(|chr| u8::from_str_radix(&chr.to_string(), 10).expect("expected digit"))
+                .map(|chr| match chr {
+                    '.' => None,
+                    _ => Some(chr.to_digit(10).expect("expected a digit or '.'") as u8),
+                })
                 .collect()
         })
         .collect()
 }
 
+/// How many summits a trailhead can reach, and by how many trails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Trailhead {
+    pub position: (usize, usize),
+    pub score: usize,
+    pub rating: usize,
+}
+
 struct Map {
-    data: Vec<Vec<u8>>,
+    data: Vec<Vec<Option<u8>>>,
+    rules: Rules,
 }
 
 impl Map {
     pub fn new_from_file() -> Self {
-        Self { data: get_map() }
+        Self::parse(include_str!("input.txt"))
+    }
+
+    pub fn parse(input: &str) -> Self {
+        Self {
+            data: get_map(input),
+            rules: Rules::default(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_rules(self, rules: Rules) -> Self {
+        Self { rules, ..self }
     }
 
-    pub fn get(&self, coord: (usize, usize)) -> u8 {
+    /// The height of a cell, or `None` if it can't be walked on.
+    pub fn get(&self, coord: (usize, usize)) -> Option<u8> {
         self.data[coord.0][coord.1]
     }
 
@@ -33,56 +64,165 @@ impl Map {
         self.data[0].len()
     }
 
-    pub fn trailheads(&self) -> Vec<(usize, usize)> {
-        self.data
-            .iter()
-            .enumerate()
-            .flat_map(|(row_num, row)| {
-                row.iter()
-                    .enumerate()
-                    .filter(|(_, cell)| **cell == 0)
-                    .map(move |(col, _)| (row_num, col))
-            })
-            .collect()
+    /// The cells next to a cell, up, left, down, then right, followed by the
+    /// diagonals with eight neighbours.
+    fn neighbours(&self, coord: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, 0),
+            (0, -1),
+            (1, 0),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ];
+        let count = match self.rules.connectivity {
+            Connectivity::Four => 4,
+            Connectivity::Eight => 8,
+        };
+        let map_height = self.height();
+        let map_width = self.width();
+        OFFSETS[..count].iter().filter_map(move |(dy, dx)| {
+            let y = coord
+                .0
+                .checked_add_signed(*dy)
+                .filter(|y| *y < map_height)?;
+            let x = coord.1.checked_add_signed(*dx).filter(|x| *x < map_width)?;
+            Some((y, x))
+        })
     }
 
-    pub fn dfs(&self, coord: (usize, usize)) -> Vec<(usize, usize)> {
-        let val = self.get(coord);
-        if val == 9 {
-            // println!("{:#?}", coord);
-            return vec![coord];
+    /// The cells a trail can go to next from a cell.
+    fn steps(&self, coord: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let height = self.get(coord);
+        self.neighbours(coord).filter(move |next| {
+            height
+                .zip(self.get(*next))
+                .is_some_and(|(from, to)| self.rules.allows_step(from, to))
+        })
+    }
+
+    fn is_trailhead(&self, coord: (usize, usize)) -> bool {
+        self.get(coord) == Some(self.rules.start)
+    }
+
+    fn is_summit(&self, coord: (usize, usize)) -> bool {
+        self.get(coord) == Some(self.rules.end)
+    }
+
+    /// Scores and rates every trailhead, ordered by position.
+    pub fn score_trailheads(&self) -> Vec<Trailhead> {
+        if self.rules.is_monotone() {
+            self.score_monotone_trailheads()
+        } else {
+            (0..self.height())
+                .flat_map(|y| (0..self.width()).map(move |x| (y, x)))
+                .filter(|coord| self.is_trailhead(*coord))
+                .map(|coord| self.walk_trailhead(coord))
+                .collect()
         }
-        let map_height = self.height();
-        let map_width = self.width();
-        let valid_checks = [
-            (coord.0 > 0).then(|| (coord.0 - 1, coord.1)),
-            (coord.1 > 0).then(|| (coord.0, coord.1 - 1)),
-            (coord.0 < map_height - 1).then(|| (coord.0 + 1, coord.1)),
-            (coord.1 < map_width - 1).then(|| (coord.0, coord.1 + 1)),
-        ];
-        // a lot of collecting vecs going on
-        valid_checks
-            .into_iter()
-            .flat_map(|check| {
-                check
-                    .filter(|next| self.get(*next) == val + 1)
-                    .map(|coord| self.dfs(coord))
-                    .unwrap_or_default()
+    }
+
+    /// Scores and rates every trailhead at once, by working back from the
+    /// cells trails get to last: the highest if every step climbs, or the
+    /// lowest if every step descends. Each cell gets the set of summits it can
+    /// reach, and the number of trails to them, from the cells it can step to,
+    /// which are already done.
+    fn score_monotone_trailheads(&self) -> Vec<Trailhead> {
+        let width = self.width();
+        let mut cells = vec![];
+        let mut summit_count = 0;
+        for y in 0..self.height() {
+            for x in 0..width {
+                if let Some(height) = self.get((y, x)) {
+                    cells.push((height, (y, x)));
+                    summit_count += self.is_summit((y, x)) as usize;
+                }
+            }
+        }
+        if self.rules.only_descends() {
+            cells.sort_by_key(|(height, _)| *height);
+        } else {
+            cells.sort_by_key(|(height, _)| std::cmp::Reverse(*height));
+        }
+
+        let mut summits = vec![BitSet::new(summit_count); self.height() * width];
+        let mut trails = vec![0; self.height() * width];
+        let mut next_summit = 0;
+        for &(_, coord) in &cells {
+            let index = coord.0 * width + coord.1;
+            // trails end at the first summit they reach
+            if self.is_summit(coord) {
+                summits[index].insert(next_summit);
+                trails[index] = 1;
+                next_summit += 1;
+                continue;
+            }
+            for next in self.steps(coord) {
+                let next_index = next.0 * width + next.1;
+                let above = std::mem::take(&mut summits[next_index]);
+                summits[index].union_with(&above);
+                summits[next_index] = above;
+                trails[index] += trails[next_index];
+            }
+        }
+
+        (0..self.height())
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .filter(|coord| self.is_trailhead(*coord))
+            .map(|(y, x)| Trailhead {
+                position: (y, x),
+                score: summits[y * width + x].len(),
+                rating: trails[y * width + x],
             })
             .collect()
     }
+
+    /// Scores and rates a trailhead by walking every trail from it, for rules
+    /// where a trail could go back to a cell it's already been on, which it
+    /// isn't allowed to.
+    fn walk_trailhead(&self, trailhead: (usize, usize)) -> Trailhead {
+        let width = self.width();
+        let mut visited = vec![false; self.height() * width];
+        let mut summits = BitSet::new(self.height() * width);
+        let mut rating = 0;
+
+        let mut stack = vec![(trailhead, false)];
+        while let Some((coord, leaving)) = stack.pop() {
+            let index = coord.0 * width + coord.1;
+            if leaving {
+                visited[index] = false;
+                continue;
+            }
+            if self.is_summit(coord) {
+                summits.insert(index);
+                rating += 1;
+                continue;
+            }
+            visited[index] = true;
+            stack.push((coord, true));
+            for next in self.steps(coord) {
+                if !visited[next.0 * width + next.1] {
+                    stack.push((next, false));
+                }
+            }
+        }
+
+        Trailhead {
+            position: trailhead,
+            score: summits.len(),
+            rating,
+        }
+    }
 }
 
 fn part_1() {
     let map = Map::new_from_file();
     let sum: usize = map
-        .trailheads()
-        .clone()
+        .score_trailheads()
         .iter()
-        .map(|&trailhead| {
-            // println!("trailhead: {:#?}", trailhead);
-            map.dfs(trailhead).into_iter().unique().count()
-        })
+        .map(|trailhead| trailhead.score)
         .sum();
 
     println!("{sum}");
@@ -91,13 +231,9 @@ fn part_1() {
 fn part_2() {
     let map = Map::new_from_file();
     let sum: usize = map
-        .trailheads()
-        .clone()
+        .score_trailheads()
         .iter()
-        .map(|&trailhead| {
-            // println!("trailhead: {:#?}", trailhead);
-            map.dfs(trailhead).len()
-        })
+        .map(|trailhead| trailhead.rating)
         .sum();
 
     println!("{sum}");
@@ -107,3 +243,105 @@ pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints the first few trails as JSON and on the map, and saves every trail
+/// drawn on the map as a PNG.
+pub fn debug() {
+    let map = Map::new_from_file();
+    let trails = map.trails(10);
+    println!("{}", trails::to_json(&trails));
+    print!("{}", render::to_terminal(&map, &trails));
+    render::debug(&map, &map.trails(usize::MAX), "trails.png");
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "89010123\n78121874\n87430965\n96549874\n\
+                       45678903\n32019012\n01329801\n10456732";
+
+#[test]
+fn test_score_trailheads() {
+    let map = Map::parse(EXAMPLE);
+    let trailheads = map.score_trailheads();
+    assert_eq!(trailheads.len(), 9);
+    assert_eq!(
+        trailheads
+            .iter()
+            .map(|trailhead| trailhead.score)
+            .sum::<usize>(),
+        36
+    );
+    assert_eq!(
+        trailheads
+            .iter()
+            .map(|trailhead| trailhead.rating)
+            .sum::<usize>(),
+        81
+    );
+    assert_eq!(
+        trailheads[0],
+        Trailhead {
+            position: (0, 2),
+            score: 5,
+            rating: 20,
+        }
+    );
+}
+
+#[test]
+fn test_impassable() {
+    let map = Map::parse("...0...\n...1...\n...2...\n6543456\n7.....7\n8.....8\n9.....9");
+    assert_eq!(map.score_trailheads()[0].score, 2);
+
+    let map = Map::parse(".....0.\n..4321.\n..5..2.\n..6543.\n..7..4.\n..8765.\n..9....");
+    assert_eq!(map.score_trailheads()[0].rating, 3);
+}
+
+#[test]
+fn test_rules() {
+    let score = |map: &str, deltas, connectivity| {
+        Map::parse(map)
+            .with_rules(Rules {
+                deltas,
+                start: 0,
+                end: 9,
+                connectivity,
+            })
+            .score_trailheads()
+            .iter()
+            .map(|trailhead| (trailhead.score, trailhead.rating))
+            .collect::<Vec<_>>()
+    };
+
+    // flat steps can't go back to where a trail has already been
+    assert_eq!(
+        score("00\n19", 0..=9, Connectivity::Four),
+        vec![(1, 2), (1, 2)]
+    );
+    assert_eq!(
+        score("00\n19", 0..=9, Connectivity::Eight),
+        vec![(1, 4), (1, 4)]
+    );
+    // without flat steps, each trailhead only has its own way up
+    assert_eq!(
+        score("00\n19", 1..=9, Connectivity::Four),
+        vec![(1, 1), (1, 1)]
+    );
+
+    // walking every trail agrees with working back from the summits, both
+    // going up and going down
+    let climbing = Map::parse(EXAMPLE);
+    let descending = Map::parse(EXAMPLE).with_rules(Rules {
+        deltas: -1..=-1,
+        start: 9,
+        end: 0,
+        connectivity: Connectivity::Four,
+    });
+    for map in [climbing, descending] {
+        let walked = map
+            .score_trailheads()
+            .iter()
+            .map(|trailhead| map.walk_trailhead(trailhead.position))
+            .collect::<Vec<_>>();
+        assert_eq!(walked, map.score_trailheads());
+    }
+}
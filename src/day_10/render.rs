@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use image::{ImageBuffer, Rgb};
+
+use crate::render::PALETTE;
+
+use super::{trails::Trail, Map};
+
+const CELL_SIZE: u32 = 8;
+
+/// The colour of each cell on a trail, by the first trail that goes through it.
+fn trail_colors(trails: &[Trail]) -> HashMap<(usize, usize), [u8; 3]> {
+    let mut colors = HashMap::new();
+    for (index, trail) in trails.iter().enumerate() {
+        for cell in trail {
+            colors
+                .entry(*cell)
+                .or_insert(PALETTE[index % PALETTE.len()]);
+        }
+    }
+    colors
+}
+
+/// Draws the heights with ANSI colours, dimming every cell that isn't on a trail.
+/// Impassable cells are drawn as `.`.
+pub fn to_terminal(map: &Map, trails: &[Trail]) -> String {
+    let colors = trail_colors(trails);
+    let mut output = String::new();
+    for (y, row) in map.data.iter().enumerate() {
+        for (x, height) in row.iter().enumerate() {
+            let Some(height) = height else {
+                output.push('.');
+                continue;
+            };
+            match colors.get(&(y, x)) {
+                Some([r, g, b]) => output += &format!("\x1b[1;38;2;{r};{g};{b}m{height}\x1b[0m"),
+                None => output += &format!("\x1b[2m{height}\x1b[0m"),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Saves the map as a PNG, shading heights from dark to light, with the trails
+/// drawn on top.
+pub fn debug(map: &Map, trails: &[Trail], image_name: &str) {
+    let colors = trail_colors(trails);
+    let image = ImageBuffer::from_fn(
+        map.width() as u32 * CELL_SIZE,
+        map.height() as u32 * CELL_SIZE,
+        |x, y| {
+            let cell = ((y / CELL_SIZE) as usize, (x / CELL_SIZE) as usize);
+            // impassable cells are left black
+            let shade = map.get(cell).map_or(0, |height| 20 + height * 15);
+            Rgb(colors.get(&cell).copied().unwrap_or([shade; 3]))
+        },
+    );
+    image.save(image_name).unwrap();
+}
+
+#[test]
+fn test_to_terminal() {
+    let map = Map::parse("0123\n1234\n8765\n9876");
+    let rendered = to_terminal(&map, &map.trails(1));
+    let lines = rendered.lines().collect::<Vec<_>>();
+    // the first trail goes down from the trailhead, then along the middle rows
+    assert_eq!(lines[0].matches("\x1b[2m").count(), 3);
+    assert_eq!(lines[1].matches("\x1b[1;38;2;230;80;80m").count(), 4);
+    assert_eq!(lines[2].matches("\x1b[1;38;2;230;80;80m").count(), 4);
+    assert_eq!(lines[3].matches("\x1b[2m").count(), 3);
+}
@@ -0,0 +1,53 @@
+use std::ops::RangeInclusive;
+
+/// Which cells count as next to each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, left, down and right.
+    #[default]
+    Four,
+    /// The diagonals too.
+    #[allow(dead_code)]
+    Eight,
+}
+
+/// What makes a path a hiking trail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// How much the height can change with each step, which can be negative.
+    pub deltas: RangeInclusive<i16>,
+    /// The height of trailheads.
+    pub start: u8,
+    /// The height of summits, where trails end.
+    pub end: u8,
+    pub connectivity: Connectivity,
+}
+
+impl Default for Rules {
+    /// The puzzle's rules, one step up at a time from 0 to 9.
+    fn default() -> Self {
+        Self {
+            deltas: 1..=1,
+            start: 0,
+            end: 9,
+            connectivity: Connectivity::Four,
+        }
+    }
+}
+
+impl Rules {
+    pub fn allows_step(&self, from: u8, to: u8) -> bool {
+        self.deltas.contains(&(to as i16 - from as i16))
+    }
+
+    /// Whether every step goes up, or every step goes down, so no trail can
+    /// visit a cell twice.
+    pub fn is_monotone(&self) -> bool {
+        *self.deltas.start() > 0 || *self.deltas.end() < 0
+    }
+
+    /// Whether every step goes down.
+    pub fn only_descends(&self) -> bool {
+        *self.deltas.end() < 0
+    }
+}
@@ -0,0 +1,119 @@
+use itertools::Itertools;
+
+use super::Map;
+
+/// The cells of a trail, from its trailhead to its summit.
+pub type Trail = Vec<(usize, usize)>;
+
+impl Map {
+    /// Every distinct trail from a trailhead, stopping after `limit` of them.
+    pub fn trails_from(&self, trailhead: (usize, usize), limit: usize) -> Vec<Trail> {
+        let mut trails = vec![];
+        if limit == 0 || !self.is_trailhead(trailhead) {
+            return trails;
+        }
+        if self.is_summit(trailhead) {
+            trails.push(vec![trailhead]);
+            return trails;
+        }
+
+        // reversed, so that popping them goes in the order of `neighbours`
+        let steps = |cell, path: &Trail| {
+            let mut steps = self
+                .steps(cell)
+                .filter(|next| !path.contains(next))
+                .collect::<Vec<_>>();
+            steps.reverse();
+            steps
+        };
+        // each cell of the trail so far, with the cells it can go to next
+        let mut path = vec![trailhead];
+        let mut stack = vec![steps(trailhead, &path)];
+        while let Some(nexts) = stack.last_mut() {
+            let Some(next) = nexts.pop() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            path.push(next);
+            if self.is_summit(next) {
+                trails.push(path.clone());
+                if trails.len() == limit {
+                    break;
+                }
+                path.pop();
+            } else {
+                stack.push(steps(next, &path));
+            }
+        }
+        trails
+    }
+
+    /// Every distinct trail on the map, ordered by trailhead, stopping after
+    /// `limit` of them.
+    pub fn trails(&self, limit: usize) -> Vec<Trail> {
+        let mut trails = vec![];
+        for (y, x) in (0..self.height()).cartesian_product(0..self.width()) {
+            if trails.len() >= limit {
+                break;
+            }
+            trails.extend(self.trails_from((y, x), limit - trails.len()));
+        }
+        trails
+    }
+}
+
+/// Writes trails as a JSON array of objects, with coordinates as `[y, x]`.
+pub fn to_json(trails: &[Trail]) -> String {
+    let coord = |(y, x): &(usize, usize)| format!("[{y}, {x}]");
+    let objects = trails
+        .iter()
+        .filter_map(|trail| {
+            Some(format!(
+                "  {{\"trailhead\": {}, \"summit\": {}, \"cells\": [{}]}}",
+                coord(trail.first()?),
+                coord(trail.last()?),
+                trail.iter().map(coord).join(", ")
+            ))
+        })
+        .join(",\n");
+    if objects.is_empty() {
+        "[]".to_string()
+    } else {
+        format!("[\n{objects}\n]")
+    }
+}
+
+#[test]
+fn test_trails() {
+    let map = Map::parse(super::EXAMPLE);
+    assert_eq!(map.trails(usize::MAX).len(), 81);
+    assert_eq!(map.trails(10).len(), 10);
+    assert!(map.trails(usize::MAX).iter().all_unique());
+
+    let trails = map.trails_from((0, 2), usize::MAX);
+    assert_eq!(trails.len(), 20);
+    for trail in &trails {
+        assert_eq!(trail.len(), 10);
+        for (height, cell) in trail.iter().enumerate() {
+            assert_eq!(map.get(*cell), Some(height as u8));
+        }
+        for (from, to) in trail.iter().tuple_windows() {
+            assert_eq!(from.0.abs_diff(to.0) + from.1.abs_diff(to.1), 1);
+        }
+    }
+}
+
+#[test]
+fn test_to_json() {
+    let map = Map::parse("0123\n1234\n8765\n9876");
+    let trails = map.trails(2);
+    assert_eq!(
+        to_json(&trails),
+        "[\n  {\"trailhead\": [0, 0], \"summit\": [3, 0], \"cells\": \
+         [[0, 0], [1, 0], [1, 1], [1, 2], [1, 3], [2, 3], [2, 2], [2, 1], [2, 0], [3, 0]]},\n  \
+         {\"trailhead\": [0, 0], \"summit\": [3, 0], \"cells\": \
+         [[0, 0], [1, 0], [1, 1], [1, 2], [1, 3], [2, 3], [2, 2], [2, 1], [3, 1], [3, 0]]}\n]"
+    );
+    assert_eq!(to_json(&[]), "[]");
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// How many stones there are with some value, which only ever needs adding up.
+pub trait Count: Clone + fmt::Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// `None` if the sum is too big for the type.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+impl Count for u128 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    /// Overflows after a couple of hundred blinks with the puzzle's rules, so
+    /// use `BigCount` for more than that.
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u128::checked_add(*self, *other)
+    }
+}
+
+/// An unsigned integer of any size, as little endian base 2^64 digits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigCount {
+    digits: Vec<u64>,
+}
+
+impl Count for BigCount {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn one() -> Self {
+        Self { digits: vec![1] }
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut sum = self.clone();
+        if sum.digits.len() < other.digits.len() {
+            sum.digits.resize(other.digits.len(), 0);
+        }
+        let mut carry = false;
+        for (index, digit) in sum.digits.iter_mut().enumerate() {
+            let other = other.digits.get(index).copied().unwrap_or(0);
+            let (sum, overflowed) = digit.overflowing_add(other);
+            let (sum, carried) = sum.overflowing_add(carry as u64);
+            *digit = sum;
+            carry = overflowed || carried;
+        }
+        if carry {
+            sum.digits.push(1);
+        }
+        Some(sum)
+    }
+}
+
+impl fmt::Display for BigCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // divides by 10^19 over and over, the largest power of 10 in a u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut digits = self.digits.clone();
+        let mut chunks = vec![];
+        while digits.iter().any(|digit| *digit != 0) {
+            let mut remainder = 0;
+            for digit in digits.iter_mut().rev() {
+                let value = (remainder as u128) << 64 | *digit as u128;
+                *digit = (value / CHUNK as u128) as u64;
+                remainder = (value % CHUNK as u128) as u64;
+            }
+            chunks.push(remainder);
+        }
+
+        let Some((last, rest)) = chunks.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{last}")?;
+        for chunk in rest.iter().rev() {
+            write!(f, "{chunk:019}")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_big_count() {
+    let mut count = BigCount::one();
+    assert_eq!(count.to_string(), "1");
+    assert_eq!(BigCount::zero().to_string(), "0");
+
+    // doubling past a u128, to 2^130
+    for _ in 0..130 {
+        count = count.checked_add(&count).unwrap();
+    }
+    assert_eq!(
+        count.to_string(),
+        "1361129467683753853853498429727072845824"
+    );
+}
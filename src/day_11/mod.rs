@@ -1,72 +1,218 @@
-use std::collections::HashMap;
-
-fn transform(stone: usize) -> (usize, Option<usize>) {
-    let digit_count = stone.checked_ilog10().unwrap_or(0) + 1;
-    if stone == 0 {
-        (1, None)
-    } else if digit_count % 2 == 0 {
-        let right_side = stone % 10usize.pow(digit_count / 2);
-        let left_side = stone / 10usize.pow(digit_count / 2);
-        (left_side, Some(right_side))
-    } else {
-        (stone * 2024, None)
-    }
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+
+use count::Count;
+use rules::{Overflow, Rules};
+
+use crate::parse;
+
+mod count;
+mod rules;
+
+/// A stone that isn't a number. Entries are counted from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InvalidStone {
+    entry: usize,
+    text: String,
 }
 
-#[test]
-fn test_transform() {
-    assert_eq!(transform(0), (1, None));
-    assert_eq!(transform(1234), (12, Some(34)));
-    assert_eq!(transform(123), (123 * 2024, None));
+impl fmt::Display for InvalidStone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entry {}: expected a stone, found {:?}",
+            self.entry, self.text
+        )
+    }
 }
 
-fn part_1() {
-    let mut stones = vec![0, 7, 6618216, 26481, 885, 42, 202642, 8791];
-
-    for _ in 0..25 {
-        let mut new_stones = stones
-            .iter_mut()
-            .filter_map(|stone| {
-                let new_stones = transform(*stone);
-                *stone = new_stones.0;
-                new_stones.1
+fn parse(input: &str) -> Result<Vec<u64>, InvalidStone> {
+    input
+        .split_whitespace()
+        .enumerate()
+        .map(|(entry, text)| {
+            parse::digits(text).ok_or_else(|| InvalidStone {
+                entry,
+                text: text.to_string(),
             })
-            .collect::<Vec<_>>();
-        stones.append(&mut new_stones);
+        })
+        .collect()
+}
+
+fn get_input() -> Vec<u64> {
+    parse(include_str!("input.txt")).unwrap_or_else(|error| panic!("{error}"))
+}
+
+/// Why stones couldn't be counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CountError {
+    Stone(Overflow),
+    /// There were too many stones for the type they were counted with, after
+    /// this many blinks.
+    TooMany {
+        blinks: usize,
+    },
+}
+
+impl From<Overflow> for CountError {
+    fn from(overflow: Overflow) -> Self {
+        CountError::Stone(overflow)
     }
+}
 
-    println!("{}", stones.len());
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountError::Stone(overflow) => write!(f, "{overflow}"),
+            CountError::TooMany { blinks } => {
+                write!(f, "too many stones to count after {blinks} blinks")
+            }
+        }
+    }
 }
 
-fn multiply_stones(stone: usize, steps: usize, seen: &mut HashMap<(usize, usize), usize>) -> usize {
-    if let Some(count) = seen.get(&(stone, steps)) {
-        return *count;
+/// How many stones there are after blinking, keeping only how many stones
+/// there are of each value, since stones with the same value all change the
+/// same way. Only a few thousand values ever turn up with the puzzle's rules,
+/// so what each one turns into is worked out once and each blink takes about
+/// the same time however many stones there are.
+fn count_stones<C: Count>(rules: &Rules, stones: &[u64], blinks: usize) -> Result<C, CountError> {
+    let mut counts: HashMap<u64, C> = HashMap::new();
+    for stone in stones {
+        add_stones(&mut counts, *stone, &C::one()).ok_or(CountError::TooMany { blinks: 0 })?;
     }
-    if steps == 0 {
-        return 1;
+
+    let mut transformed: HashMap<u64, Vec<u64>> = HashMap::new();
+    for blink in 1..=blinks {
+        let mut next_counts = HashMap::with_capacity(counts.len());
+        for (stone, count) in &counts {
+            if !transformed.contains_key(stone) {
+                let mut next_stones = vec![];
+                rules.apply(*stone, &mut next_stones)?;
+                transformed.insert(*stone, next_stones);
+            }
+            for next in &transformed[stone] {
+                add_stones(&mut next_counts, *next, count)
+                    .ok_or(CountError::TooMany { blinks: blink })?;
+            }
+        }
+        counts = next_counts;
     }
-    let next = transform(stone);
-    let result = multiply_stones(next.0, steps - 1, seen)
-        + next
-            .1
-            .map(|other| multiply_stones(other, steps - 1, seen))
-            .unwrap_or(0);
-    seen.insert((stone, steps), result);
-    result
+
+    counts
+        .into_values()
+        .try_fold(C::zero(), |total, count| total.checked_add(&count))
+        .ok_or(CountError::TooMany { blinks })
 }
 
-fn part_2() {
-    let stones = vec![0, 7, 6618216, 26481, 885, 42, 202642, 8791];
-    let mut seen = HashMap::new();
-    let sum: usize = stones
-        .iter()
-        .map(|stone| multiply_stones(*stone, 75, &mut seen))
-        .sum();
+/// Adds `count` stones with a value, or `None` if that's too many to count.
+fn add_stones<C: Count>(counts: &mut HashMap<u64, C>, stone: u64, count: &C) -> Option<()> {
+    match counts.entry(stone) {
+        Entry::Occupied(mut entry) => {
+            let total = entry.get().checked_add(count)?;
+            entry.insert(total);
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(count.clone());
+        }
+    }
+    Some(())
+}
 
-    println!("{sum}");
+fn part_1() {
+    let stones = get_input();
+    let count: u128 =
+        count_stones(&Rules::default(), &stones, 25).unwrap_or_else(|error| panic!("{error}"));
+    println!("{count}");
+}
+
+fn part_2() {
+    let stones = get_input();
+    let count: u128 =
+        count_stones(&Rules::default(), &stones, 75).unwrap_or_else(|error| panic!("{error}"));
+    println!("{count}");
 }
 
 pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints the count after far more blinks than a u128 can hold, and after 75
+/// blinks with the puzzle's rules changed to multiply by 2025.
+pub fn debug() {
+    let stones = get_input();
+    let count: count::BigCount =
+        count_stones(&Rules::default(), &stones, 1000).unwrap_or_else(|error| panic!("{error}"));
+    println!("{count}");
+
+    let rules = "0 -> 1\neven digits -> split\nany -> * 2025"
+        .parse()
+        .unwrap_or_else(|error| panic!("{error}"));
+    match count_stones::<u128>(&rules, &stones, 75) {
+        Ok(count) => println!("{count}"),
+        Err(error) => println!("{error}"),
+    }
+}
+
+#[test]
+fn test_count_stones() {
+    use count::BigCount;
+
+    let rules = Rules::default();
+    let stones = parse("125 17\n").unwrap();
+    assert_eq!(count_stones::<u128>(&rules, &stones, 6), Ok(22));
+    assert_eq!(count_stones::<u128>(&rules, &stones, 25), Ok(55312));
+    assert_eq!(count_stones::<u128>(&rules, &[], 25), Ok(0));
+
+    // the same counts, until a u128 is too small for them
+    assert_eq!(
+        count_stones::<BigCount>(&rules, &stones, 150).map(|count| count.to_string()),
+        count_stones::<u128>(&rules, &stones, 150).map(|count| count.to_string())
+    );
+    let count = count_stones::<BigCount>(&rules, &stones, 1000).unwrap();
+    assert!(count.to_string().len() > 39);
+    assert!(matches!(
+        count_stones::<u128>(&rules, &stones, 300),
+        Err(CountError::TooMany { blinks }) if blinks > 150 && blinks <= 300
+    ));
+}
+
+#[test]
+fn test_rule_variants() {
+    // without splitting, stones only ever grow, until they overflow
+    let rules = "0 -> 1\nany -> * 2024".parse().unwrap();
+    assert_eq!(count_stones::<u128>(&rules, &[0, 1], 3), Ok(2));
+    assert_eq!(
+        count_stones::<u128>(&rules, &[1], 10),
+        Err(CountError::Stone(Overflow {
+            stone: 2024u64.pow(5),
+            rule: 1
+        }))
+    );
+
+    // a stone that turns into three each blink
+    let rules = "any -> 0, 0, 0".parse().unwrap();
+    assert_eq!(count_stones::<u128>(&rules, &[5], 4), Ok(81));
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(parse("0 1 10 99 999\n"), Ok(vec![0, 1, 10, 99, 999]));
+    assert_eq!(
+        parse("125 +17"),
+        Err(InvalidStone {
+            entry: 1,
+            text: "+17".to_string()
+        })
+    );
+    assert_eq!(
+        parse("125 17 x"),
+        Err(InvalidStone {
+            entry: 2,
+            text: "x".to_string()
+        })
+    );
+}
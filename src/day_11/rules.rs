@@ -0,0 +1,292 @@
+use std::{fmt, str::FromStr};
+
+use crate::parse;
+
+/// Which stones a rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predicate {
+    Equals(u64),
+    EvenDigits,
+    OddDigits,
+    Digits(u32),
+    MultipleOf(u64),
+    Any,
+}
+
+impl Predicate {
+    pub fn matches(&self, stone: u64) -> bool {
+        match *self {
+            Predicate::Equals(value) => stone == value,
+            Predicate::EvenDigits => digit_count(stone).is_multiple_of(2),
+            Predicate::OddDigits => !digit_count(stone).is_multiple_of(2),
+            Predicate::Digits(count) => digit_count(stone) == count,
+            Predicate::MultipleOf(value) => stone.is_multiple_of(value),
+            Predicate::Any => true,
+        }
+    }
+}
+
+/// One of the things a stone turns into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// A stone with this value, whatever the old one was.
+    Value(u64),
+    Add(u64),
+    Mul(u64),
+    /// Two stones, one with the first half of the digits and one with the
+    /// rest, so the second gets the extra digit when there's an odd number.
+    Split,
+}
+
+/// The puzzle's rules, the first one that matches a stone applies.
+pub const PUZZLE: &str = "0 -> 1\neven digits -> split\nany -> * 2024";
+
+/// A stone matching the predicate turns into all of the outputs, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub predicate: Predicate,
+    pub outputs: Vec<Output>,
+}
+
+/// A stone that got too big for a u64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    pub stone: u64,
+    /// Which rule it matched, counting from 0.
+    pub rule: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stone {} overflowed when changed by rule {}",
+            self.stone, self.rule
+        )
+    }
+}
+
+/// Why rules couldn't be parsed. Line numbers start from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A line without a `->` between the predicate and the outputs.
+    MissingArrow {
+        line: usize,
+        text: String,
+    },
+    InvalidPredicate {
+        line: usize,
+        text: String,
+    },
+    InvalidOutput {
+        line: usize,
+        text: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingArrow { line, text } => write!(
+                f,
+                "line {line}: expected a rule like `0 -> 1`, found {text:?}"
+            ),
+            ParseError::InvalidPredicate { line, text } => write!(
+                f,
+                "line {line}: expected `N`, `even digits`, `odd digits`, `N digits`, \
+                 `multiple of N` or `any`, found {text:?}"
+            ),
+            ParseError::InvalidOutput { line, text } => write!(
+                f,
+                "line {line}: expected `N`, `+ N`, `* N` or `split`, found {text:?}"
+            ),
+        }
+    }
+}
+
+/// How stones change with each blink, by the first rule that matches them.
+/// Stones no rule matches stay as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub rules: Vec<Rule>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        PUZZLE
+            .parse()
+            .expect("expected the puzzle's rules to parse")
+    }
+}
+
+impl FromStr for Rules {
+    type Err = ParseError;
+
+    /// Parses a rule per line, like `even digits -> split` or `any -> 1, * 3`,
+    /// skipping blank lines.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let rules = input
+            .lines()
+            .enumerate()
+            .map(|(index, text)| (index + 1, text.trim()))
+            .filter(|(_, text)| !text.is_empty())
+            .map(|(line, text)| {
+                let (predicate, outputs) =
+                    text.split_once("->")
+                        .ok_or_else(|| ParseError::MissingArrow {
+                            line,
+                            text: text.to_string(),
+                        })?;
+                let predicate = parse_predicate(predicate.trim()).ok_or_else(|| {
+                    ParseError::InvalidPredicate {
+                        line,
+                        text: predicate.trim().to_string(),
+                    }
+                })?;
+                let outputs = outputs
+                    .split(',')
+                    .map(|output| {
+                        parse_output(output.trim()).ok_or_else(|| ParseError::InvalidOutput {
+                            line,
+                            text: output.trim().to_string(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Rule { predicate, outputs })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+}
+
+impl Rules {
+    /// Adds the stones a stone turns into to `stones`.
+    pub fn apply(&self, stone: u64, stones: &mut Vec<u64>) -> Result<(), Overflow> {
+        let Some((rule, Rule { outputs, .. })) = self
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.predicate.matches(stone))
+        else {
+            stones.push(stone);
+            return Ok(());
+        };
+
+        let overflow = Overflow { stone, rule };
+        for output in outputs {
+            match *output {
+                Output::Value(value) => stones.push(value),
+                Output::Add(value) => stones.push(stone.checked_add(value).ok_or(overflow)?),
+                Output::Mul(value) => stones.push(stone.checked_mul(value).ok_or(overflow)?),
+                Output::Split => {
+                    let digits = digit_count(stone);
+                    let divisor = 10u64.pow(digits - digits / 2);
+                    stones.extend([stone / divisor, stone % divisor]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn digit_count(stone: u64) -> u32 {
+    stone.checked_ilog10().unwrap_or(0) + 1
+}
+
+fn parse_predicate(text: &str) -> Option<Predicate> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    match words[..] {
+        ["any"] => Some(Predicate::Any),
+        ["even", "digits"] => Some(Predicate::EvenDigits),
+        ["odd", "digits"] => Some(Predicate::OddDigits),
+        ["multiple", "of", value] => Some(Predicate::MultipleOf(
+            parse::digits(value).filter(|value| *value > 0)?,
+        )),
+        [count, "digits"] => Some(Predicate::Digits(parse::digits(count)?)),
+        [value] => Some(Predicate::Equals(parse::digits(value)?)),
+        _ => None,
+    }
+}
+
+fn parse_output(text: &str) -> Option<Output> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    match words[..] {
+        ["split"] => Some(Output::Split),
+        ["+", value] => Some(Output::Add(parse::digits(value)?)),
+        ["*", value] => Some(Output::Mul(parse::digits(value)?)),
+        [value] => Some(Output::Value(parse::digits(value)?)),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_puzzle_rules() {
+    let rules = Rules::default();
+    let apply = |stone| {
+        let mut stones = vec![];
+        rules.apply(stone, &mut stones).map(|_| stones)
+    };
+    assert_eq!(apply(0), Ok(vec![1]));
+    assert_eq!(apply(1234), Ok(vec![12, 34]));
+    assert_eq!(apply(1000), Ok(vec![10, 0]));
+    assert_eq!(apply(123), Ok(vec![123 * 2024]));
+    assert_eq!(
+        apply(u64::MAX / 1000),
+        Err(Overflow {
+            stone: u64::MAX / 1000,
+            rule: 2
+        })
+    );
+}
+
+#[test]
+fn test_parse() {
+    let rules: Rules = "multiple of 3 -> split, + 1\n\n5 digits -> 0\nodd digits -> * 3"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        rules.rules,
+        vec![
+            Rule {
+                predicate: Predicate::MultipleOf(3),
+                outputs: vec![Output::Split, Output::Add(1)],
+            },
+            Rule {
+                predicate: Predicate::Digits(5),
+                outputs: vec![Output::Value(0)],
+            },
+            Rule {
+                predicate: Predicate::OddDigits,
+                outputs: vec![Output::Mul(3)],
+            },
+        ]
+    );
+    let mut stones = vec![];
+    rules.apply(123, &mut stones).unwrap();
+    rules.apply(12344, &mut stones).unwrap();
+    rules.apply(7, &mut stones).unwrap();
+    rules.apply(10, &mut stones).unwrap();
+    assert_eq!(stones, vec![1, 23, 124, 0, 21, 10]);
+
+    assert_eq!(
+        "0 => 1".parse::<Rules>(),
+        Err(ParseError::MissingArrow {
+            line: 1,
+            text: "0 => 1".to_string()
+        })
+    );
+    assert_eq!(
+        "\nmultiple of 0 -> 1".parse::<Rules>(),
+        Err(ParseError::InvalidPredicate {
+            line: 2,
+            text: "multiple of 0".to_string()
+        })
+    );
+    assert_eq!(
+        "any -> - 1".parse::<Rules>(),
+        Err(ParseError::InvalidOutput {
+            line: 1,
+            text: "- 1".to_string()
+        })
+    );
+}
@@ -0,0 +1,121 @@
+/// What an instruction does to the machine when it is executed.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Effect {
+    /// Replaces the accumulator with the result of the operation, given the
+    /// current accumulator and the instruction's arguments. `None` means the
+    /// operation overflowed. Skipped while the machine is disabled.
+    Apply(fn(u64, &[u64]) -> Option<u64>),
+    Enable,
+    Disable,
+    /// Saves whether the machine is currently enabled, for a later `PopScope`.
+    PushScope,
+    /// Restores the enabled state saved by the matching `PushScope`.
+    PopScope,
+}
+
+/// Declares an instruction of the form `name(arg,arg,...)`.
+#[derive(Debug, Clone)]
+pub struct InstructionSpec {
+    pub name: String,
+    pub arity: usize,
+    /// The most digits accepted in a single argument.
+    pub max_digits: usize,
+    pub effect: Effect,
+}
+
+impl InstructionSpec {
+    pub fn new(name: &str, arity: usize, effect: Effect) -> Self {
+        Self {
+            name: name.to_string(),
+            arity,
+            max_digits: 3,
+            effect,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_digits(mut self, max_digits: usize) -> Self {
+        self.max_digits = max_digits;
+        self
+    }
+}
+
+/// Adds the product of the arguments to the accumulator.
+pub fn mul(acc: u64, args: &[u64]) -> Option<u64> {
+    let product = args
+        .iter()
+        .try_fold(1_u64, |product, arg| product.checked_mul(*arg))?;
+    acc.checked_add(product)
+}
+
+/// Adds the sum of the arguments to the accumulator.
+#[allow(dead_code)]
+pub fn add(acc: u64, args: &[u64]) -> Option<u64> {
+    args.iter().try_fold(acc, |acc, arg| acc.checked_add(*arg))
+}
+
+/// Subtracts the sum of the arguments from the accumulator.
+#[allow(dead_code)]
+pub fn sub(acc: u64, args: &[u64]) -> Option<u64> {
+    args.iter().try_fold(acc, |acc, arg| acc.checked_sub(*arg))
+}
+
+/// An instruction read from memory. The opcode indexes into the set it was lexed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: usize,
+    pub args: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    specs: Vec<InstructionSpec>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The instructions from the puzzle: `mul(a,b)`, `do()` and `don't()`.
+    pub fn puzzle() -> Self {
+        Self::new()
+            .register(InstructionSpec::new("mul", 2, Effect::Apply(mul)))
+            .register(InstructionSpec::new("do", 0, Effect::Enable))
+            .register(InstructionSpec::new("don't", 0, Effect::Disable))
+    }
+
+    pub fn register(mut self, spec: InstructionSpec) -> Self {
+        // restricting names keeps the lexer from having to backtrack:
+        // no instruction can be found inside of another one's name or arguments
+        assert!(
+            !spec.name.is_empty()
+                && spec
+                    .name
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphabetic() || byte == b'_' || byte == b'\''),
+            "expected instruction name {:?} to only contain letters, '_' or '\\''",
+            spec.name
+        );
+        assert!(
+            (1..=19).contains(&spec.max_digits),
+            "expected between 1 and 19 digits per argument to fit in a u64"
+        );
+        assert!(
+            self.specs.iter().all(|other| other.name != spec.name),
+            "instruction {:?} is already registered",
+            spec.name
+        );
+        self.specs.push(spec);
+        self
+    }
+
+    pub fn specs(&self) -> &[InstructionSpec] {
+        &self.specs
+    }
+
+    pub fn get(&self, opcode: usize) -> &InstructionSpec {
+        &self.specs[opcode]
+    }
+}
@@ -0,0 +1,262 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use super::instructions::{Instruction, InstructionSet, InstructionSpec};
+
+/// An instruction found in the corrupted memory, along with the byte offset it starts at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub instruction: Instruction,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Stage {
+    // number of bytes of the name matched so far
+    Name(usize),
+    // waiting on the ')' of an instruction without arguments
+    Close,
+    Args {
+        args: Vec<u64>,
+        value: u64,
+        digits: usize,
+    },
+}
+
+enum Advance {
+    Continue,
+    Fail,
+    Complete(Vec<u64>),
+}
+
+/// A candidate instruction which has matched every byte since `start`.
+#[derive(Debug, Clone)]
+struct Partial {
+    opcode: usize,
+    start: usize,
+    stage: Stage,
+}
+
+impl Partial {
+    fn advance(&mut self, spec: &InstructionSpec, byte: u8) -> Advance {
+        match &mut self.stage {
+            Stage::Name(matched) => {
+                let name = spec.name.as_bytes();
+                if *matched < name.len() {
+                    if name[*matched] != byte {
+                        return Advance::Fail;
+                    }
+                    *matched += 1;
+                } else if byte == b'(' {
+                    self.stage = if spec.arity == 0 {
+                        Stage::Close
+                    } else {
+                        Stage::Args {
+                            args: Vec::with_capacity(spec.arity),
+                            value: 0,
+                            digits: 0,
+                        }
+                    };
+                } else {
+                    return Advance::Fail;
+                }
+                Advance::Continue
+            }
+            Stage::Close => match byte {
+                b')' => Advance::Complete(vec![]),
+                _ => Advance::Fail,
+            },
+            Stage::Args {
+                args,
+                value,
+                digits,
+            } => match byte {
+                b'0'..=b'9' if *digits < spec.max_digits => {
+                    *value = *value * 10 + u64::from(byte - b'0');
+                    *digits += 1;
+                    Advance::Continue
+                }
+                b',' if *digits > 0 && args.len() + 1 < spec.arity => {
+                    args.push(*value);
+                    *value = 0;
+                    *digits = 0;
+                    Advance::Continue
+                }
+                b')' if *digits > 0 && args.len() + 1 == spec.arity => {
+                    args.push(*value);
+                    Advance::Complete(std::mem::take(args))
+                }
+                _ => Advance::Fail,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Scanner<'a> {
+    set: &'a InstructionSet,
+    // opcodes of the instructions whose name starts with each byte
+    starts: Vec<Vec<usize>>,
+    partials: Vec<Partial>,
+    // offset of the next byte to be scanned
+    offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(set: &'a InstructionSet) -> Self {
+        let mut starts = vec![vec![]; 256];
+        for (opcode, spec) in set.specs().iter().enumerate() {
+            starts[spec.name.as_bytes()[0] as usize].push(opcode);
+        }
+        Self {
+            set,
+            starts,
+            partials: vec![],
+            offset: 0,
+        }
+    }
+
+    /// Advances every candidate by one byte, returning a token if one completes.
+    fn step(&mut self, byte: u8) -> Option<Token> {
+        let offset = self.offset;
+        self.offset += 1;
+
+        let set = self.set;
+        let mut completed: Option<Token> = None;
+        self.partials.retain_mut(|partial| {
+            match partial.advance(set.get(partial.opcode), byte) {
+                Advance::Continue => true,
+                Advance::Fail => false,
+                Advance::Complete(args) => {
+                    // prefer the leftmost match, like a regex would
+                    if completed
+                        .as_ref()
+                        .is_none_or(|token| partial.start < token.offset)
+                    {
+                        completed = Some(Token {
+                            instruction: Instruction {
+                                opcode: partial.opcode,
+                                args,
+                            },
+                            offset: partial.start,
+                        });
+                    }
+                    false
+                }
+            }
+        });
+
+        if completed.is_some() {
+            // anything else still matching overlaps with the completed instruction
+            self.partials.clear();
+            return completed;
+        }
+
+        self.partials
+            .extend(self.starts[byte as usize].iter().map(|&opcode| Partial {
+                opcode,
+                start: offset,
+                stage: Stage::Name(1),
+            }));
+        None
+    }
+}
+
+/// Streams instruction tokens out of any reader, without holding more than a
+/// single buffer of the input in memory at a time.
+pub struct Lexer<'a, R> {
+    reader: BufReader<R>,
+    scanner: Scanner<'a>,
+}
+
+impl<'a, R: Read> Lexer<'a, R> {
+    pub fn new(reader: R, set: &'a InstructionSet) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            scanner: Scanner::new(set),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Lexer<'_, R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Some(Err(err)),
+            };
+            if buf.is_empty() {
+                return None;
+            }
+
+            let mut consumed = 0;
+            let mut token = None;
+            for &byte in buf {
+                consumed += 1;
+                token = self.scanner.step(byte);
+                if token.is_some() {
+                    break;
+                }
+            }
+            self.reader.consume(consumed);
+
+            if let Some(token) = token {
+                return Some(Ok(token));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn lex(input: &str, set: &InstructionSet) -> Vec<(String, Vec<u64>, usize)> {
+    Lexer::new(input.as_bytes(), set)
+        .map(|token| {
+            let token = token.unwrap();
+            let name = set.get(token.instruction.opcode).name.clone();
+            (name, token.instruction.args, token.offset)
+        })
+        .collect()
+}
+
+#[test]
+fn test_lexer() {
+    let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    let tokens = lex(input, &InstructionSet::puzzle());
+    let expected = [
+        ("mul", vec![2, 4], 1),
+        ("don't", vec![], 20),
+        ("mul", vec![5, 5], 28),
+        ("mul", vec![11, 8], 48),
+        ("do", vec![], 59),
+        ("mul", vec![8, 5], 64),
+    ];
+    assert_eq!(
+        tokens,
+        expected.map(|(name, args, offset)| (name.to_string(), args, offset))
+    );
+}
+
+#[test]
+fn test_lexer_rejects_long_arguments() {
+    let tokens = lex("mul(1234,5)mmul(12,345)", &InstructionSet::puzzle());
+    assert_eq!(tokens, vec![("mul".to_string(), vec![12, 345], 12)]);
+}
+
+#[test]
+fn test_lexer_overlapping_names() {
+    use super::instructions::{add, Effect};
+
+    // the "d" in "add" starts a "do()" that has to be found
+    let set = InstructionSet::puzzle()
+        .register(InstructionSpec::new("add", 3, Effect::Apply(add)).with_max_digits(5));
+    let tokens = lex("addo()add(1,22222,3)", &set);
+    assert_eq!(
+        tokens,
+        vec![
+            ("do".to_string(), vec![], 2),
+            ("add".to_string(), vec![1, 22222, 3], 6),
+        ]
+    );
+}
@@ -1,79 +1,112 @@
-use regex::Regex;
+use itertools::Itertools;
 
-fn part_1() {
-    let input = include_str!("input.txt");
-    let regex_matcher = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
-    let sum: u32 = regex_matcher
-        .captures_iter(input)
-        .map(|c| {
-            let (_, [a, b]) = c.extract();
-            u32::from_str_radix(a, 10).unwrap() * u32::from_str_radix(b, 10).unwrap()
-        })
-        .sum();
-    println!("{sum}");
+use instructions::{Effect, InstructionSet, InstructionSpec};
+use lexer::{Lexer, Token};
+
+pub mod instructions;
+pub mod lexer;
+
+fn tokens(set: &InstructionSet) -> impl Iterator<Item = Token> + '_ {
+    let input = include_bytes!("input.txt");
+    Lexer::new(input.as_slice(), set).map(|token| token.expect("expected to read input"))
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Instruction {
-    Mul(u32, u32),
-    On,
-    Off,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachineError {
+    /// The accumulator over- or underflowed at the instruction at this offset.
+    Overflow { offset: usize },
+    /// A scope was closed at this offset without having been opened.
+    UnbalancedScope { offset: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceEntry {
+    pub token: Token,
+    /// False if the instruction was skipped because the machine was disabled.
+    pub applied: bool,
 }
 
 #[derive(Debug)]
-struct Machine {
+struct Machine<'a> {
+    set: &'a InstructionSet,
     is_on: bool,
-    sum: u32,
+    scopes: Vec<bool>,
+    sum: u64,
+    trace: Option<Vec<TraceEntry>>,
 }
 
-impl Machine {
-    pub fn new() -> Self {
+impl<'a> Machine<'a> {
+    pub fn new(set: &'a InstructionSet) -> Self {
         Machine {
+            set,
             is_on: true,
+            scopes: vec![],
             sum: 0,
+            trace: None,
         }
     }
 
-    pub fn instruct(&mut self, instruction: Instruction) {
-        match instruction {
-            Instruction::Mul(a, b) => {
+    /// Records every instruction given to the machine, and whether it was applied.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    pub fn instruct(&mut self, token: Token) -> Result<(), MachineError> {
+        let offset = token.offset;
+        let mut applied = true;
+        match self.set.get(token.instruction.opcode).effect {
+            Effect::Apply(operation) => {
                 if self.is_on {
-                    self.sum += a * b;
+                    self.sum = operation(self.sum, &token.instruction.args)
+                        .ok_or(MachineError::Overflow { offset })?;
+                } else {
+                    applied = false;
                 }
             }
-            Instruction::Off => self.is_on = false,
-            Instruction::On => self.is_on = true,
+            Effect::Enable => self.is_on = true,
+            Effect::Disable => self.is_on = false,
+            Effect::PushScope => self.scopes.push(self.is_on),
+            Effect::PopScope => {
+                self.is_on = self
+                    .scopes
+                    .pop()
+                    .ok_or(MachineError::UnbalancedScope { offset })?
+            }
         }
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry { token, applied });
+        }
+        Ok(())
+    }
+
+    pub fn run(mut self, tokens: impl Iterator<Item = Token>) -> Result<Self, MachineError> {
+        for token in tokens {
+            self.instruct(token)?;
+        }
+        Ok(self)
     }
 }
 
-fn part_2() {
-    let input = include_str!("input.txt");
-    let regex_matcher: Regex =
-        Regex::new(r"(mul)\((\d{1,3}),(\d{1,3})\)|(do)\(\)|(don\'t)\(\)").unwrap();
-    let sum = regex_matcher
-        .captures_iter(input)
-        .map(|c| {
-            let components = c.iter().skip(1).collect::<Vec<_>>();
-            if components[0].is_some() {
-                Instruction::Mul(
-                    u32::from_str_radix(components[1].unwrap().as_str(), 10).unwrap(),
-                    u32::from_str_radix(components[2].unwrap().as_str(), 10).unwrap(),
-                )
-            } else if components[3].is_some() {
-                Instruction::On
-            } else if components[4].is_some() {
-                Instruction::Off
-            } else {
-                panic!("shouldn't be possible");
-            }
-        })
-        .fold(Machine::new(), |mut machine, instr| {
-            machine.instruct(instr);
-            machine
-        })
+fn part_1() {
+    let set = InstructionSet::new().register(InstructionSpec::new(
+        "mul",
+        2,
+        Effect::Apply(instructions::mul),
+    ));
+    let sum = Machine::new(&set)
+        .run(tokens(&set))
+        .expect("expected the machine to run")
         .sum;
+    println!("{sum}");
+}
 
+fn part_2() {
+    let set = InstructionSet::puzzle();
+    let sum = Machine::new(&set)
+        .run(tokens(&set))
+        .expect("expected the machine to run")
+        .sum;
     println!("{sum}");
 }
 
@@ -81,3 +114,71 @@ pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints every instruction the machine was given, with its offset and
+/// whether it was applied or skipped while the machine was off, then how many
+/// were skipped.
+pub fn debug() {
+    let set = InstructionSet::puzzle();
+    let machine = Machine::new(&set)
+        .with_trace()
+        .run(tokens(&set))
+        .expect("expected the machine to run");
+    let trace = machine.trace.unwrap_or_default();
+    for TraceEntry { token, applied } in &trace {
+        let name = &set.get(token.instruction.opcode).name;
+        let args = token.instruction.args.iter().join(",");
+        let status = if *applied { "applied" } else { "skipped" };
+        println!("{:>6}: {name}({args}) {status}", token.offset);
+    }
+    let skipped = trace.iter().filter(|entry| !entry.applied).count();
+    println!("{skipped} of {} instructions skipped", trace.len());
+}
+
+#[test]
+fn test_nested_scopes() {
+    let set = InstructionSet::puzzle()
+        .register(InstructionSpec::new(
+            "add",
+            2,
+            Effect::Apply(instructions::add),
+        ))
+        .register(InstructionSpec::new(
+            "sub",
+            1,
+            Effect::Apply(instructions::sub),
+        ))
+        .register(InstructionSpec::new("push", 0, Effect::PushScope))
+        .register(InstructionSpec::new("pop", 0, Effect::PopScope));
+    let input = "add(1,2)push()don't()mul(5,5)push()do()sub(1)pop()add(9,9)pop()mul(2,3)";
+    let machine = Machine::new(&set)
+        .with_trace()
+        .run(Lexer::new(input.as_bytes(), &set).map(Result::unwrap))
+        .unwrap();
+    assert_eq!(machine.sum, 1 + 2 - 1 + 2 * 3);
+    let skipped = machine
+        .trace
+        .unwrap()
+        .into_iter()
+        .filter(|entry| !entry.applied)
+        .map(|entry| entry.token.offset)
+        .collect::<Vec<_>>();
+    assert_eq!(skipped, vec![21, 50]);
+
+    let unbalanced =
+        Machine::new(&set).run(Lexer::new("pop()".as_bytes(), &set).map(Result::unwrap));
+    assert_eq!(
+        unbalanced.unwrap_err(),
+        MachineError::UnbalancedScope { offset: 0 }
+    );
+}
+
+#[test]
+fn test_overflow() {
+    let set = InstructionSet::new().register(
+        InstructionSpec::new("mul", 2, Effect::Apply(instructions::mul)).with_max_digits(10),
+    );
+    let input = "mul(4294967296,4294967296)";
+    let result = Machine::new(&set).run(Lexer::new(input.as_bytes(), &set).map(Result::unwrap));
+    assert_eq!(result.unwrap_err(), MachineError::Overflow { offset: 0 });
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use image::{ImageBuffer, Rgb};
+
+use crate::render::PALETTE;
+
+use super::search::Match;
+use super::template::{Placement, Template};
+
+const DIM: [u8; 3] = [70, 70, 70];
+const CELL_SIZE: u32 = 8;
+
+/// The grid cells covered by found words or shapes, each with the colour of what covered it.
+#[derive(Debug, Clone, Default)]
+pub struct Highlights {
+    cells: HashMap<(usize, usize), usize>,
+}
+
+impl Highlights {
+    /// Colours the cells of each word search match by which word was found.
+    /// Words from a wrapping search carry on from the opposite edge.
+    pub fn from_matches(grid: &[Vec<char>], matches: &[Match], words: &[&str]) -> Self {
+        let height = grid.len() as isize;
+        let width = grid.first().map(Vec::len).unwrap_or(0) as isize;
+        let mut highlights = Self::default();
+        for found in matches {
+            let (dy, dx) = found.direction.offset();
+            let (y, x) = found.position;
+            for step in 0..words[found.word].chars().count() as isize {
+                let cell = (
+                    (y as isize + dy * step).rem_euclid(height) as usize,
+                    (x as isize + dx * step).rem_euclid(width) as usize,
+                );
+                highlights.cells.entry(cell).or_insert(found.word);
+            }
+        }
+        highlights
+    }
+
+    /// Colours the non-wildcard cells of each placement by which template was placed.
+    pub fn from_placements(placements: &[Placement], templates: &[Template]) -> Self {
+        let mut highlights = Self::default();
+        for placement in placements {
+            let (y, x) = placement.position;
+            for (offset_y, offset_x) in templates[placement.template].fixed_cells() {
+                highlights
+                    .cells
+                    .entry((y + offset_y, x + offset_x))
+                    .or_insert(placement.template);
+            }
+        }
+        highlights
+    }
+
+    fn color(&self, cell: (usize, usize)) -> Option<[u8; 3]> {
+        self.cells
+            .get(&cell)
+            .map(|index| PALETTE[index % PALETTE.len()])
+    }
+}
+
+/// Renders the grid with ANSI colours, dimming every cell that isn't highlighted.
+pub fn to_terminal(grid: &[Vec<char>], highlights: &Highlights) -> String {
+    let mut output = String::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, chr) in row.iter().enumerate() {
+            match highlights.color((y, x)) {
+                Some([r, g, b]) => output += &format!("\x1b[1;38;2;{r};{g};{b}m{chr}\x1b[0m"),
+                None => output += &format!("\x1b[2m{chr}\x1b[0m"),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Saves the grid as a PNG with a square per cell, coloured like `to_terminal`.
+pub fn debug(grid: &[Vec<char>], highlights: &Highlights, image_name: &str) {
+    let height = grid.len() as u32;
+    let width = grid.first().map(Vec::len).unwrap_or(0) as u32;
+    let image = ImageBuffer::from_fn(width * CELL_SIZE, height * CELL_SIZE, |x, y| {
+        let cell = ((y / CELL_SIZE) as usize, (x / CELL_SIZE) as usize);
+        // leave a gap between cells so that neighbouring words stay distinguishable
+        if x % CELL_SIZE == 0 || y % CELL_SIZE == 0 {
+            return Rgb([0, 0, 0]);
+        }
+        Rgb(highlights.color(cell).unwrap_or(DIM))
+    });
+    image.save(image_name).unwrap();
+}
+
+#[test]
+fn test_to_terminal() {
+    use super::search::WordSearch;
+
+    let grid = vec!["XMAS".chars().collect::<Vec<_>>(), "ABCD".chars().collect()];
+    let matches = WordSearch::new(&["XMAS"]).find(&grid);
+    let rendered = to_terminal(&grid, &Highlights::from_matches(&grid, &matches, &["XMAS"]));
+    let lines = rendered.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0].matches("\x1b[1;38;2;230;80;80m").count(), 4);
+    assert_eq!(lines[1].matches("\x1b[2m").count(), 4);
+
+    // "XM" on the right edge carries on with "AS" on the left
+    let grid = vec!["ASXM".chars().collect::<Vec<_>>(), "BBBB".chars().collect()];
+    let matches = WordSearch::new(&["XMAS"]).with_wrap(true).find(&grid);
+    let highlights = Highlights::from_matches(&grid, &matches, &["XMAS"]);
+    for x in 0..4 {
+        assert!(highlights.color((0, x)).is_some());
+        assert!(highlights.color((1, x)).is_none());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// The (y, x) step taken when reading in this direction.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::UpRight => (-1, 1),
+            Direction::Right => (0, 1),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (1, 0),
+            Direction::DownLeft => (1, -1),
+            Direction::Left => (0, -1),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::Right => Direction::Left,
+            Direction::DownRight => Direction::UpLeft,
+            Direction::Down => Direction::Up,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::Left => Direction::Right,
+            Direction::UpLeft => Direction::DownRight,
+        }
+    }
+}
+
+/// A word found in the grid, read from `position` (y, x) in `direction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// Index of the word in the list the search was built with.
+    pub word: usize,
+    pub position: (usize, usize),
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pattern {
+    word: usize,
+    len: usize,
+    reversed: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    next: HashMap<char, usize>,
+    fail: usize,
+    // patterns ending at this node, including those reached through failure links
+    outputs: Vec<usize>,
+}
+
+/// Aho-Corasick automaton, so every word is matched in a single pass over a line.
+#[derive(Debug, Clone)]
+struct Automaton {
+    nodes: Vec<Node>,
+    patterns: Vec<Pattern>,
+}
+
+impl Automaton {
+    fn new(words: &[Vec<char>]) -> Self {
+        let mut nodes = vec![Node::default()];
+        let mut patterns = vec![];
+
+        // each word is added backwards too, so that scanning a line once finds
+        // the words reading in both of its directions
+        for (word, chars) in words.iter().enumerate() {
+            for reversed in [false, true] {
+                let mut node = 0;
+                let mut add_char = |chr: char| {
+                    node = match nodes[node].next.get(&chr) {
+                        Some(&next) => next,
+                        None => {
+                            nodes.push(Node::default());
+                            let next = nodes.len() - 1;
+                            nodes[node].next.insert(chr, next);
+                            next
+                        }
+                    }
+                };
+                if reversed {
+                    chars.iter().rev().for_each(|&chr| add_char(chr));
+                } else {
+                    chars.iter().for_each(|&chr| add_char(chr));
+                }
+                nodes[node].outputs.push(patterns.len());
+                patterns.push(Pattern {
+                    word,
+                    len: chars.len(),
+                    reversed,
+                });
+            }
+        }
+
+        // breadth first, so that the failure target of a node is always complete
+        let mut queue = nodes[0].next.values().copied().collect::<VecDeque<_>>();
+        while let Some(node) = queue.pop_front() {
+            let children = nodes[node]
+                .next
+                .iter()
+                .map(|(&chr, &child)| (chr, child))
+                .collect::<Vec<_>>();
+            for (chr, child) in children {
+                let mut fail = nodes[node].fail;
+                let target = loop {
+                    if let Some(&target) = nodes[fail].next.get(&chr) {
+                        break target;
+                    }
+                    if fail == 0 {
+                        break 0;
+                    }
+                    fail = nodes[fail].fail;
+                };
+                nodes[child].fail = target;
+                let inherited = nodes[target].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        Self { nodes, patterns }
+    }
+
+    fn step(&self, mut node: usize, chr: char) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].next.get(&chr) {
+                return next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+}
+
+/// Finds every occurrence of a set of words in a grid, in all 8 directions.
+#[derive(Debug, Clone)]
+pub struct WordSearch {
+    automaton: Automaton,
+    longest: usize,
+    wrap: bool,
+}
+
+impl WordSearch {
+    pub fn new(words: &[&str]) -> Self {
+        let words = words
+            .iter()
+            .map(|word| word.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert!(
+            words.iter().all(|word| !word.is_empty()),
+            "expected words to be non-empty"
+        );
+        Self {
+            automaton: Automaton::new(&words),
+            longest: words.iter().map(Vec::len).max().unwrap_or(0),
+            wrap: false,
+        }
+    }
+
+    /// Lets words continue off one edge of the grid onto the opposite edge.
+    #[allow(dead_code)]
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn find(&self, grid: &[Vec<char>]) -> Vec<Match> {
+        let mut matches = vec![];
+        if grid.is_empty() || grid[0].is_empty() {
+            return matches;
+        }
+        // half of the directions, since each scan also finds the reversed words
+        for direction in [
+            Direction::Right,
+            Direction::DownRight,
+            Direction::Down,
+            Direction::DownLeft,
+        ] {
+            for line in lines(grid, direction, self.wrap) {
+                self.scan(grid, &line, direction, &mut matches);
+            }
+        }
+        matches
+    }
+
+    fn scan(
+        &self,
+        grid: &[Vec<char>],
+        line: &Line,
+        direction: Direction,
+        matches: &mut Vec<Match>,
+    ) {
+        let cells = &line.cells;
+        // a wrapping line is a cycle, so it is read again past its end until
+        // every word starting inside of it has had a chance to finish
+        let scan_len = if line.cyclic {
+            cells.len() + self.longest - 1
+        } else {
+            cells.len()
+        };
+
+        let mut node = 0;
+        for end in 0..scan_len {
+            let (y, x) = cells[end % cells.len()];
+            node = self.automaton.step(node, grid[y][x]);
+            for &pattern in &self.automaton.nodes[node].outputs {
+                let pattern = self.automaton.patterns[pattern];
+                let Some(start) = (end + 1).checked_sub(pattern.len) else {
+                    continue;
+                };
+                if start >= cells.len() {
+                    continue;
+                }
+                matches.push(if pattern.reversed {
+                    Match {
+                        word: pattern.word,
+                        position: cells[end % cells.len()],
+                        direction: direction.opposite(),
+                    }
+                } else {
+                    Match {
+                        word: pattern.word,
+                        position: cells[start],
+                        direction,
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    cells: Vec<(usize, usize)>,
+    cyclic: bool,
+}
+
+/// Every line of cells through the grid in a direction. Without wrapping these
+/// run edge to edge, and with it they are the cycles of stepping around the torus.
+fn lines(grid: &[Vec<char>], direction: Direction, wrap: bool) -> Vec<Line> {
+    let height = grid.len() as isize;
+    let width = grid[0].len() as isize;
+    let (dy, dx) = direction.offset();
+
+    let mut seen = vec![vec![false; width as usize]; height as usize];
+    let mut lines = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            if seen[y as usize][x as usize] {
+                continue;
+            }
+            // without wrapping, lines start on the cell with no predecessor
+            if !wrap {
+                let (prev_y, prev_x) = (y - dy, x - dx);
+                if prev_y >= 0 && prev_y < height && prev_x >= 0 && prev_x < width {
+                    continue;
+                }
+            }
+
+            let mut cells = vec![];
+            let (mut cell_y, mut cell_x) = (y, x);
+            loop {
+                seen[cell_y as usize][cell_x as usize] = true;
+                cells.push((cell_y as usize, cell_x as usize));
+                (cell_y, cell_x) = (cell_y + dy, cell_x + dx);
+                if wrap {
+                    (cell_y, cell_x) = (cell_y.rem_euclid(height), cell_x.rem_euclid(width));
+                    if seen[cell_y as usize][cell_x as usize] {
+                        break;
+                    }
+                } else if cell_y < 0 || cell_y >= height || cell_x < 0 || cell_x >= width {
+                    break;
+                }
+            }
+            lines.push(Line {
+                cells,
+                cyclic: wrap,
+            });
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+fn grid_from(input: &str) -> Vec<Vec<char>> {
+    input.lines().map(|line| line.chars().collect()).collect()
+}
+
+#[test]
+fn test_find() {
+    let grid = grid_from(
+        "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+         XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX",
+    );
+    assert_eq!(WordSearch::new(&["XMAS"]).find(&grid).len(), 18);
+
+    let matches = WordSearch::new(&["XMAS", "SAMX", "MM"]).find(&grid);
+    // a palindrome-free word and its reverse are found at the same cells
+    assert_eq!(matches.iter().filter(|m| m.word == 0).count(), 18);
+    assert_eq!(matches.iter().filter(|m| m.word == 1).count(), 18);
+    assert!(matches.contains(&Match {
+        word: 0,
+        position: (4, 6),
+        direction: Direction::Left,
+    }));
+    assert!(matches.contains(&Match {
+        word: 0,
+        position: (9, 3),
+        direction: Direction::UpLeft,
+    }));
+}
+
+#[test]
+fn test_find_wrapping() {
+    let grid = grid_from("ASXM\nXXXX\nXXXX");
+    let search = WordSearch::new(&["XMAS"]);
+    assert!(search.find(&grid).is_empty());
+
+    let matches = search.with_wrap(true).find(&grid);
+    assert!(matches.contains(&Match {
+        word: 0,
+        position: (0, 2),
+        direction: Direction::Right,
+    }));
+}
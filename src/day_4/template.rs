@@ -0,0 +1,146 @@
+/// A 2D pattern of characters, where `None` cells match anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+/// Where one of the templates in a list was found, with its top left corner at `position` (y, x).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub template: usize,
+    pub position: (usize, usize),
+}
+
+impl Template {
+    /// Reads a template from ASCII art, one row per line. `.` cells are wildcards,
+    /// and shorter lines are padded out with wildcards.
+    pub fn parse(art: &str) -> Self {
+        let width = art
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let cells = art
+            .lines()
+            .map(|line| {
+                let mut row = line
+                    .chars()
+                    .map(|chr| (chr != '.').then_some(chr))
+                    .collect::<Vec<_>>();
+                row.resize(width, None);
+                row
+            })
+            .collect::<Vec<_>>();
+        assert!(width > 0, "expected a non-empty template");
+        Self { cells }
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    /// The template turned a quarter turn clockwise.
+    pub fn rotated(&self) -> Self {
+        let height = self.height();
+        let cells = (0..self.width())
+            .map(|y| (0..height).map(|x| self.cells[height - 1 - x][y]).collect())
+            .collect();
+        Self { cells }
+    }
+
+    /// The template mirrored left to right.
+    pub fn reflected(&self) -> Self {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        Self { cells }
+    }
+
+    /// Every distinct rotation and reflection of the template, starting with itself.
+    pub fn symmetries(&self) -> Vec<Self> {
+        let mut symmetries: Vec<Self> = vec![];
+        for mut template in [self.clone(), self.reflected()] {
+            for _ in 0..4 {
+                let next = template.rotated();
+                if !symmetries.contains(&template) {
+                    symmetries.push(template);
+                }
+                template = next;
+            }
+        }
+        symmetries
+    }
+
+    /// Offsets (y, x) of the cells which aren't wildcards.
+    pub fn fixed_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.is_some())
+                .map(move |(x, _)| (y, x))
+        })
+    }
+
+    pub fn matches_at(&self, grid: &[Vec<char>], (y, x): (usize, usize)) -> bool {
+        if y + self.height() > grid.len() || x + self.width() > grid[y].len() {
+            return false;
+        }
+        self.cells.iter().enumerate().all(|(offset_y, row)| {
+            row.iter().enumerate().all(|(offset_x, cell)| {
+                cell.is_none_or(|chr| grid[y + offset_y][x + offset_x] == chr)
+            })
+        })
+    }
+}
+
+/// Every position in the grid that any of the templates matches at.
+pub fn placements(templates: &[Template], grid: &[Vec<char>]) -> Vec<Placement> {
+    let mut placements = vec![];
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            for (index, template) in templates.iter().enumerate() {
+                if template.matches_at(grid, (y, x)) {
+                    placements.push(Placement {
+                        template: index,
+                        position: (y, x),
+                    });
+                }
+            }
+        }
+    }
+    placements
+}
+
+#[test]
+fn test_symmetries() {
+    assert_eq!(Template::parse("M.S\n.A.\nM.S").symmetries().len(), 4);
+    assert_eq!(Template::parse("AB\nC").symmetries().len(), 8);
+    assert_eq!(Template::parse("A").symmetries().len(), 1);
+
+    let rotated = Template::parse("AB\nC").rotated();
+    assert_eq!(rotated, Template::parse("CA\n.B"));
+}
+
+#[test]
+fn test_placements() {
+    let grid = include_str!("test_input.txt")
+        .lines()
+        .map(|line| line.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let templates = Template::parse("M.S\n.A.\nM.S").symmetries();
+    let found = placements(&templates, &grid);
+    assert_eq!(found.len(), 9);
+    assert_eq!(
+        found[0],
+        Placement {
+            template: 0,
+            position: (0, 1),
+        }
+    );
+}
@@ -0,0 +1,123 @@
+use std::{collections::HashMap, fmt};
+
+use crate::parse;
+
+/// Page ordering rules `X|Y` and the updates to check against them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Input {
+    pub rules: Vec<(u32, u32)>,
+    pub updates: Vec<Vec<u32>>,
+}
+
+impl Input {
+    /// The pages each page has to be printed before.
+    pub fn rule_map(&self) -> HashMap<u32, Vec<u32>> {
+        self.rules.iter().fold(HashMap::new(), |mut rules, rule| {
+            rules.entry(rule.0).or_default().push(rule.1);
+            rules
+        })
+    }
+}
+
+/// Why the input couldn't be parsed. Line numbers start from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A line in the rules block which isn't two page numbers separated by `|`.
+    InvalidRule { line: usize, text: String },
+    /// A line in the updates block which isn't a comma separated list of page numbers.
+    InvalidUpdate { line: usize, text: String },
+    /// There was no blank line between the rules and the updates.
+    MissingSeparator,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidRule { line, text } => {
+                write!(
+                    f,
+                    "line {line}: expected a rule like `47|53`, found {text:?}"
+                )
+            }
+            ParseError::InvalidUpdate { line, text } => write!(
+                f,
+                "line {line}: expected an update like `75,47,61`, found {text:?}"
+            ),
+            ParseError::MissingSeparator => {
+                write!(f, "expected a blank line between the rules and updates")
+            }
+        }
+    }
+}
+
+fn parse_page(text: &str) -> Option<u32> {
+    parse::digits(text.trim())
+}
+
+/// Parses a block of rules, a blank line, then a block of updates.
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(index, text)| (index + 1, text));
+    let mut parsed = Input::default();
+
+    let mut separated = false;
+    for (line, text) in lines.by_ref() {
+        if text.trim().is_empty() {
+            separated = true;
+            break;
+        }
+        let rule = text
+            .split_once('|')
+            .and_then(|(x, y)| Some((parse_page(x)?, parse_page(y)?)))
+            .ok_or_else(|| ParseError::InvalidRule {
+                line,
+                text: text.to_string(),
+            })?;
+        parsed.rules.push(rule);
+    }
+    if !separated {
+        return Err(ParseError::MissingSeparator);
+    }
+
+    for (line, text) in lines {
+        if text.trim().is_empty() {
+            continue;
+        }
+        let update = text
+            .split(',')
+            .map(parse_page)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| ParseError::InvalidUpdate {
+                line,
+                text: text.to_string(),
+            })?;
+        parsed.updates.push(update);
+    }
+
+    Ok(parsed)
+}
+
+#[test]
+fn test_parse() {
+    let parsed = parse("47|53\n1024|7\r\n\n75,47,61\n100000,3\n").unwrap();
+    assert_eq!(parsed.rules, vec![(47, 53), (1024, 7)]);
+    assert_eq!(parsed.updates, vec![vec![75, 47, 61], vec![100000, 3]]);
+
+    assert_eq!(
+        parse("47|53\n47-53\n\n1,2"),
+        Err(ParseError::InvalidRule {
+            line: 2,
+            text: "47-53".to_string(),
+        })
+    );
+    assert_eq!(
+        parse("47|53\n\n1,,2"),
+        Err(ParseError::InvalidUpdate {
+            line: 3,
+            text: "1,,2".to_string(),
+        })
+    );
+    assert_eq!(parse("47|53\n"), Err(ParseError::MissingSeparator));
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use itertools::Itertools;
+
+use super::order::{order, OrderError};
+
+/// A rule `X|Y` broken by an update, because `Y` was printed before `X`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: (u32, u32),
+    /// Where `X` and `Y` are in the update.
+    pub positions: (usize, usize),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ((x, y), (x_pos, y_pos)) = (self.rule, self.positions);
+        write!(
+            f,
+            "{x}|{y} is broken: {y} at position {y_pos} comes before {x} at position {x_pos}"
+        )
+    }
+}
+
+/// Every rule broken by the update, ordered by where the violations are.
+pub fn violations(updates: &[u32], rules: &HashMap<u32, Vec<u32>>) -> Vec<Violation> {
+    let positions: HashMap<u32, usize> = updates
+        .iter()
+        .enumerate()
+        .map(|(position, page)| (*page, position))
+        .collect();
+    let empty_vec = vec![];
+    updates
+        .iter()
+        .enumerate()
+        .flat_map(|(x_pos, &x)| {
+            let positions = &positions;
+            rules
+                .get(&x)
+                .unwrap_or(&empty_vec)
+                .iter()
+                .unique()
+                .filter_map(move |&y| {
+                    let y_pos = *positions.get(&y)?;
+                    (y_pos < x_pos).then_some(Violation {
+                        rule: (x, y),
+                        positions: (x_pos, y_pos),
+                    })
+                })
+        })
+        .sorted_by_key(|violation| (violation.positions.1, violation.positions.0))
+        .collect()
+}
+
+/// A rule `X|Y` alongside a rule `Y|X`, with the first line each is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub rule: (u32, u32),
+    pub line: usize,
+    pub reverse_line: usize,
+}
+
+/// Problems found in a list of rules. Line numbers start from 1.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintReport {
+    /// Rules which appear more than once, with every line they are on.
+    pub duplicates: Vec<((u32, u32), Vec<usize>)>,
+    pub contradictions: Vec<Contradiction>,
+    /// A cycle through the rules if there is one, in which case no order of every
+    /// page satisfies them all. Contradictions are the shortest of these.
+    pub cycle: Option<Vec<(u32, u32)>>,
+}
+
+impl LintReport {
+    #[allow(dead_code)]
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.contradictions.is_empty() && self.cycle.is_none()
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((x, y), lines) in &self.duplicates {
+            writeln!(
+                f,
+                "duplicate rule {x}|{y} on lines {}",
+                lines.iter().join(", ")
+            )?;
+        }
+        for contradiction in &self.contradictions {
+            let ((x, y), line, reverse_line) = (
+                contradiction.rule,
+                contradiction.line,
+                contradiction.reverse_line,
+            );
+            writeln!(
+                f,
+                "contradicting rules {x}|{y} on line {line} and {y}|{x} on line {reverse_line}"
+            )?;
+        }
+        match &self.cycle {
+            Some(cycle) => write!(
+                f,
+                "rules form a cycle through {} pages: {}",
+                cycle.len(),
+                cycle.iter().map(|(x, y)| format!("{x}|{y}")).join(", ")
+            ),
+            None => write!(f, "rules are acyclic"),
+        }
+    }
+}
+
+pub fn lint(rules: &[(u32, u32)]) -> LintReport {
+    let mut lines: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        lines.entry(*rule).or_default().push(index + 1);
+    }
+
+    let duplicates = lines
+        .iter()
+        .filter(|(_, lines)| lines.len() > 1)
+        .map(|(rule, lines)| (*rule, lines.clone()))
+        .sorted_by_key(|(_, lines)| lines[0])
+        .collect();
+
+    // each contradicting pair is reported once, from the rule written first
+    let contradictions = lines
+        .iter()
+        .filter_map(|(&(x, y), rule_lines)| {
+            let reverse_lines = lines.get(&(y, x))?;
+            (rule_lines[0] < reverse_lines[0]).then_some(Contradiction {
+                rule: (x, y),
+                line: rule_lines[0],
+                reverse_line: reverse_lines[0],
+            })
+        })
+        .sorted_by_key(|contradiction| contradiction.line)
+        .collect();
+
+    // ordering every page at once fails with a cycle exactly when there is one
+    let rule_map = rules.iter().fold(HashMap::new(), |mut rule_map, rule| {
+        rule_map.entry(rule.0).or_insert_with(Vec::new).push(rule.1);
+        rule_map
+    });
+    let pages = rules
+        .iter()
+        .flat_map(|&(x, y)| [x, y])
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .sorted()
+        .collect::<Vec<_>>();
+    let cycle = match order(&pages, &rule_map) {
+        Err(OrderError::Cycle(cycle)) => Some(cycle),
+        _ => None,
+    };
+
+    LintReport {
+        duplicates,
+        contradictions,
+        cycle,
+    }
+}
+
+#[test]
+fn test_violations() {
+    let rules = HashMap::from([(1, vec![2, 3]), (4, vec![1])]);
+    let found = violations(&[3, 1, 2, 4], &rules);
+    assert_eq!(
+        found,
+        vec![
+            Violation {
+                rule: (1, 3),
+                positions: (1, 0),
+            },
+            Violation {
+                rule: (4, 1),
+                positions: (3, 1),
+            },
+        ]
+    );
+    assert!(violations(&[4, 1, 2, 3], &rules).is_empty());
+}
+
+#[test]
+fn test_lint() {
+    let report = lint(&[(1, 2), (2, 3), (1, 2), (4, 5), (5, 4)]);
+    assert_eq!(report.duplicates, vec![((1, 2), vec![1, 3])]);
+    assert_eq!(
+        report.contradictions,
+        vec![Contradiction {
+            rule: (4, 5),
+            line: 4,
+            reverse_line: 5,
+        }]
+    );
+    assert!(report.cycle.is_some());
+    assert!(!report.is_clean());
+
+    assert!(lint(&[(1, 2), (2, 3), (1, 3)]).is_clean());
+}
@@ -1,87 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use input::Input;
+use lint::{lint, violations};
+use order::order;
 
-fn get_precedence_rules() -> HashMap<u32, Vec<u32>> {
-    let input = include_str!("rules.txt");
-    let rule_numbers = input
-        .lines()
-        .map(|line| {
-            (
-                u32::from_str_radix(&line[0..2], 10).unwrap(),
-                u32::from_str_radix(&line[3..5], 10).unwrap(),
-            )
-        })
-        .collect::<Vec<_>>();
+pub mod input;
+pub mod lint;
+pub mod order;
 
-    rule_numbers.iter().fold(HashMap::new(), |mut rules, rule| {
-        rules.entry(rule.0).or_default().push(rule.1);
-        rules
-    })
-}
-
-fn get_updates() -> Vec<Vec<u32>> {
-    let input = include_str!("updates.txt");
-    input
-        .lines()
-        .map(|line| {
-            line.split(",")
-                .map(|update| u32::from_str_radix(update, 10).unwrap())
-                .collect()
-        })
-        .collect()
-}
-
-fn check_precedence(updates: &Vec<u32>, rules: &HashMap<u32, Vec<u32>>) -> bool {
-    let mut previous_numbers = HashSet::new();
-    let empty_vec = vec![];
-    updates.iter().all(|num| {
-        let proceeding_numbers = rules.get(num).unwrap_or(&empty_vec);
-        let order_broken = proceeding_numbers
-            .iter()
-            .any(|proceeding| previous_numbers.contains(proceeding));
-        previous_numbers.insert(*num);
-
-        !order_broken
-    })
-}
-
-fn sort(updates: &Vec<u32>, rules: &HashMap<u32, Vec<u32>>) -> Option<Vec<u32>> {
-    let mut updates = updates.clone();
-    let mut previous_numbers = HashSet::new();
-    let empty_vec = vec![];
-    let mut resorted = false;
-    for i in 0..updates.len() {
-        let num = updates[i];
-        let proceeding_numbers = rules.get(&num).unwrap_or(&empty_vec);
-        let earlier_mistakes: HashSet<u32> = proceeding_numbers
-            .iter()
-            .filter(|proceeding| previous_numbers.contains(*proceeding))
-            .map(|a| *a)
-            .collect();
-
-        let earliest_mistake = updates
-            .iter()
-            .enumerate()
-            .find(|(_, update_val)| earlier_mistakes.contains(*update_val))
-            .map(|pair| pair.0);
-
-        if let Some(mistake_index) = earliest_mistake {
-            // move the element at i to the place right before the mistake index
-            // by slicing and using a right-shift of 1
-            updates[mistake_index..=i].rotate_right(1);
-            resorted = true
-        }
-
-        previous_numbers.insert(num);
-    }
-
-    resorted.then_some(updates)
+fn get_input() -> Input {
+    let input = include_str!("input.txt");
+    input::parse(input).unwrap_or_else(|err| panic!("{err}"))
 }
 
 fn part_1() {
-    let rules = get_precedence_rules();
-    let update_lists = get_updates();
+    let input = get_input();
+    let rules = input.rule_map();
+    let update_lists = input.updates;
     let sum = update_lists.iter().fold(0, |acc, updates| {
-        if check_precedence(updates, &rules) {
+        if violations(updates, &rules).is_empty() {
             let middle = updates[(updates.len() - 1) / 2];
             acc + middle
         } else {
@@ -93,21 +28,18 @@ fn part_1() {
 }
 
 fn part_2() {
-    // should have done a true topological sort...
-    let rules = get_precedence_rules();
-    let update_lists = get_updates();
+    let input = get_input();
+    let rules = input.rule_map();
+    let update_lists = input.updates;
     let sum: u32 = update_lists
         .iter()
+        .filter(|updates| !violations(updates, &rules).is_empty())
         .map(|updates| {
-            let resorted = sort(updates, &rules);
-            if let Some(resorted) = resorted {
-                resorted[(resorted.len() - 1) / 2]
-            } else {
-                0
-            }
+            let ordered = order(updates, &rules).expect("expected the rules to order the update");
+            ordered[(ordered.len() - 1) / 2]
         })
         .sum();
-    
+
     println!("{sum}")
 }
 
@@ -115,3 +47,9 @@ pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints what's wrong with the rules, like the cycles through them, which the
+/// real input is known to have.
+pub fn debug() {
+    println!("{}", lint(&get_input().rules));
+}
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// The rules between the pages loop back on themselves. Lists the `X|Y` rules
+    /// making up the cycle, in order.
+    Cycle(Vec<(u32, u32)>),
+    /// The rules don't say which of these two pages comes first.
+    Ambiguous(u32, u32),
+}
+
+/// Orders the pages of an update with a topological sort of the rules between them,
+/// returning an error unless the rules decide exactly one order.
+pub fn order(pages: &[u32], rules: &HashMap<u32, Vec<u32>>) -> Result<Vec<u32>, OrderError> {
+    let page_set: HashSet<u32> = pages.iter().copied().collect();
+
+    // only the rules between pages in the update matter
+    let empty_vec = vec![];
+    let successors: HashMap<u32, Vec<u32>> = page_set
+        .iter()
+        .map(|&page| {
+            let after = rules
+                .get(&page)
+                .unwrap_or(&empty_vec)
+                .iter()
+                .copied()
+                .filter(|after| page_set.contains(after))
+                .unique()
+                .collect();
+            (page, after)
+        })
+        .collect();
+
+    let mut in_degrees: HashMap<u32, usize> = page_set.iter().map(|&page| (page, 0)).collect();
+    for after in successors.values().flatten() {
+        *in_degrees.get_mut(after).unwrap() += 1;
+    }
+
+    // seed the queue in the update's order, so that the result is stable
+    let mut seeded = HashSet::new();
+    let mut queue = pages
+        .iter()
+        .copied()
+        .filter(|page| in_degrees[page] == 0 && seeded.insert(*page))
+        .collect::<VecDeque<_>>();
+    let mut ambiguity = None;
+    let mut ordered = Vec::with_capacity(page_set.len());
+    while let Some(page) = queue.pop_front() {
+        if let (None, Some(&other)) = (ambiguity, queue.front()) {
+            ambiguity = Some((page, other));
+        }
+        ordered.push(page);
+        for after in &successors[&page] {
+            let in_degree = in_degrees.get_mut(after).unwrap();
+            *in_degree -= 1;
+            if *in_degree == 0 {
+                queue.push_back(*after);
+            }
+        }
+    }
+
+    if ordered.len() < page_set.len() {
+        return Err(OrderError::Cycle(find_cycle(&successors, &in_degrees)));
+    }
+    if let Some((a, b)) = ambiguity {
+        return Err(OrderError::Ambiguous(a, b));
+    }
+    Ok(ordered)
+}
+
+/// Finds a cycle among the pages Kahn's algorithm couldn't place. Each of them still
+/// has a predecessor which couldn't be placed either, so following predecessors
+/// back from any of them has to eventually repeat.
+fn find_cycle(
+    successors: &HashMap<u32, Vec<u32>>,
+    in_degrees: &HashMap<u32, usize>,
+) -> Vec<(u32, u32)> {
+    let remaining = |page: &u32| in_degrees[page] > 0;
+    let mut predecessors: HashMap<u32, u32> = HashMap::new();
+    for (&before, afters) in successors.iter().filter(|(page, _)| remaining(page)) {
+        for &after in afters.iter().filter(|after| remaining(after)) {
+            // keep the smallest, so that the same cycle is found every time
+            predecessors
+                .entry(after)
+                .and_modify(|predecessor| *predecessor = before.min(*predecessor))
+                .or_insert(before);
+        }
+    }
+
+    let mut page = *in_degrees
+        .keys()
+        .filter(|page| remaining(page))
+        .min()
+        .unwrap();
+    let mut path = vec![];
+    let mut visited = HashMap::new();
+    while !visited.contains_key(&page) {
+        visited.insert(page, path.len());
+        path.push(page);
+        page = predecessors[&page];
+    }
+
+    // the path walks backwards along the rules, so flip it to read in rule order
+    let mut cycle = path[visited[&page]..].to_vec();
+    cycle.reverse();
+    cycle
+        .iter()
+        .zip(cycle.iter().cycle().skip(1))
+        .map(|(&before, &after)| (before, after))
+        .collect()
+}
+
+#[cfg(test)]
+fn rules_from(pairs: &[(u32, u32)]) -> HashMap<u32, Vec<u32>> {
+    pairs.iter().fold(HashMap::new(), |mut rules, rule| {
+        rules.entry(rule.0).or_default().push(rule.1);
+        rules
+    })
+}
+
+#[test]
+fn test_order() {
+    let rules = rules_from(&[(1, 2), (2, 3), (1, 3), (3, 9)]);
+    assert_eq!(order(&[3, 1, 2], &rules), Ok(vec![1, 2, 3]));
+    assert_eq!(order(&[1, 3, 2], &rules), Ok(vec![1, 2, 3]));
+}
+
+#[test]
+fn test_order_errors() {
+    let rules = rules_from(&[(1, 2), (1, 3)]);
+    assert_eq!(order(&[1, 2, 3], &rules), Err(OrderError::Ambiguous(2, 3)));
+
+    let rules = rules_from(&[(1, 2), (2, 3), (3, 1), (3, 4)]);
+    let Err(OrderError::Cycle(mut cycle)) = order(&[4, 3, 2, 1], &rules) else {
+        panic!("expected a cycle");
+    };
+    let start = cycle.iter().position(|rule| rule.0 == 1).unwrap();
+    cycle.rotate_left(start);
+    assert_eq!(cycle, vec![(1, 2), (2, 3), (3, 1)]);
+}
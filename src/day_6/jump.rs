@@ -0,0 +1,127 @@
+use super::{BitSet, Direction, Map};
+
+/// For every cell and direction, the cell the guard stops on before the next
+/// obstacle, or `None` if they would walk off the map instead.
+#[derive(Debug, Clone)]
+pub struct JumpTable {
+    size: (usize, usize),
+    // indexed by direction, then by `y * width + x`
+    stops: [Vec<Option<(usize, usize)>>; 4],
+}
+
+/// How many steps it takes to walk from `from` to `to`, if `to` is straight ahead.
+fn distance(from: (usize, usize), to: (usize, usize), direction: Direction) -> Option<usize> {
+    match direction {
+        Direction::Up => (from.1 == to.1 && to.0 < from.0).then(|| from.0 - to.0),
+        Direction::Right => (from.0 == to.0 && to.1 > from.1).then(|| to.1 - from.1),
+        Direction::Down => (from.1 == to.1 && to.0 > from.0).then(|| to.0 - from.0),
+        Direction::Left => (from.0 == to.0 && to.1 < from.1).then(|| from.1 - to.1),
+    }
+}
+
+fn advance(from: (usize, usize), steps: usize, direction: Direction) -> (usize, usize) {
+    match direction {
+        Direction::Up => (from.0 - steps, from.1),
+        Direction::Right => (from.0, from.1 + steps),
+        Direction::Down => (from.0 + steps, from.1),
+        Direction::Left => (from.0, from.1 - steps),
+    }
+}
+
+impl JumpTable {
+    pub fn new(map: &Map) -> Self {
+        let (height, width) = map.size;
+        let mut stops: [Vec<Option<(usize, usize)>>; 4] =
+            std::array::from_fn(|_| vec![None; height * width]);
+
+        for direction in [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ] {
+            let stops = &mut stops[direction.index()];
+            // sweep each line against the direction of travel, so the nearest
+            // obstacle ahead of every cell has already been seen
+            let lines: Vec<Vec<(usize, usize)>> = match direction {
+                Direction::Up => (0..width)
+                    .map(|x| (0..height).map(|y| (y, x)).collect())
+                    .collect(),
+                Direction::Down => (0..width)
+                    .map(|x| (0..height).rev().map(|y| (y, x)).collect())
+                    .collect(),
+                Direction::Left => (0..height)
+                    .map(|y| (0..width).map(|x| (y, x)).collect())
+                    .collect(),
+                Direction::Right => (0..height)
+                    .map(|y| (0..width).rev().map(|x| (y, x)).collect())
+                    .collect(),
+            };
+            for line in lines {
+                // cells before the first obstacle in the sweep lead off the map
+                let mut stop = None;
+                let mut after_obstacle = false;
+                for (y, x) in line {
+                    if map.is_obstacle((y, x)) {
+                        after_obstacle = true;
+                        continue;
+                    }
+                    // the first open cell after an obstacle is where the guard stops
+                    if after_obstacle {
+                        stop = Some((y, x));
+                        after_obstacle = false;
+                    }
+                    stops[y * width + x] = stop;
+                }
+            }
+        }
+
+        Self {
+            size: map.size,
+            stops,
+        }
+    }
+
+    /// Where the guard stops walking from `position`, accounting for one extra obstacle.
+    pub fn jump(
+        &self,
+        position: (usize, usize),
+        direction: Direction,
+        extra_obstacle: Option<(usize, usize)>,
+    ) -> Option<(usize, usize)> {
+        let stop = self.stops[direction.index()][position.0 * self.size.1 + position.1];
+        let Some(extra_distance) =
+            extra_obstacle.and_then(|extra| distance(position, extra, direction))
+        else {
+            return stop;
+        };
+        let stop_distance = stop.map(|stop| distance(position, stop, direction).unwrap_or(0));
+        if stop_distance.is_none_or(|stop_distance| extra_distance <= stop_distance) {
+            Some(advance(position, extra_distance - 1, direction))
+        } else {
+            stop
+        }
+    }
+
+    /// Follows the guard from one turn to the next, with an extra obstacle, and
+    /// returns true if they end up walking in a loop. `turns` is scratch space
+    /// for 4 bits per cell, which is cleared before use.
+    pub fn loops(
+        &self,
+        turns: &mut BitSet,
+        mut position: (usize, usize),
+        mut direction: Direction,
+        extra_obstacle: (usize, usize),
+    ) -> bool {
+        turns.clear();
+        let cells = self.size.0 * self.size.1;
+        while let Some(stop) = self.jump(position, direction, Some(extra_obstacle)) {
+            if !turns.insert(direction.index() * cells + stop.0 * self.size.1 + stop.1) {
+                return true;
+            }
+            position = stop;
+            direction = direction.rotate_right();
+        }
+        false
+    }
+}
@@ -1,4 +1,12 @@
-use std::collections::HashSet;
+use std::thread;
+
+use jump::JumpTable;
+
+use crate::bitset::BitSet;
+
+mod jump;
+mod patrol;
+mod render;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
@@ -23,17 +31,74 @@ impl Direction {
             Direction::Left => Direction::Up,
         }
     }
+
+    pub fn from_glyph(glyph: char) -> Option<Self> {
+        match glyph {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Right => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+        }
+    }
 }
 
+/// The parts of the map that stay the same while guards walk around it, so
+/// that any number of walks can share it.
 #[derive(Debug, Default, Clone)]
 struct Map {
-    // returns None when the guard has left the map
+    size: (usize, usize),
+    // indexed by `y * width + x`
+    obstacles: Vec<bool>,
+    // where each guard starts, and which way they face
+    guards: Vec<((usize, usize), Direction)>,
+}
+
+/// One call to `Map::move_guard`: the guard at `position` facing `direction`
+/// either stepped forward, or turned right where they stood.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    pub position: (usize, usize),
+    pub direction: Direction,
+    pub turned: bool,
+}
+
+/// The state of a guard walking the map.
+#[derive(Debug, Clone)]
+struct Walk {
+    // None when the guard has left the map
     guard: Option<(usize, usize)>,
     guard_direction: Direction,
-    visited: HashSet<(usize, usize)>,
-    visited_direction: HashSet<(Direction, usize, usize)>,
-    size: (usize, usize),
-    obstacles: HashSet<(usize, usize)>,
+    visited: BitSet,
+    // indexed by `direction * cells + cell`
+    visited_direction: BitSet,
+    trace: Option<Vec<Step>>,
+}
+
+impl Walk {
+    /// Records every step of the walk.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    /// Where the guard first took the step they are about to repeat, if they're in a loop.
+    pub fn loop_start(&self) -> Option<usize> {
+        let guard = self.guard?;
+        self.trace
+            .as_ref()?
+            .iter()
+            .position(|step| step.position == guard && step.direction == self.guard_direction)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -44,79 +109,126 @@ enum MapFinishCondition {
 
 impl Map {
     pub fn new_from_file() -> Self {
-        let input = include_str!("input.txt");
-        let mut map = Map::default();
+        Self::parse(include_str!("input.txt"))
+    }
+
+    pub fn parse(input: &str) -> Self {
         let height = input.lines().count();
-        let width = input.lines().nth(0).unwrap().chars().count();
-
-        map.size = (height, width);
-        input
-            .lines()
-            .enumerate()
-            .fold(map, |mut map, (line_index, line)| {
-                line.chars()
-                    .enumerate()
-                    .for_each(|(char_index, char)| match char {
-                        '#' => {
-                            map.obstacles.insert((line_index, char_index));
-                        }
-                        '^' => {
-                            map.guard = Some((line_index, char_index));
-                            map.guard_direction = Direction::Up;
-                        }
-                        _ => {}
-                    });
-                map
-            })
+        let width = input.lines().next().unwrap().chars().count();
+
+        let mut map = Map {
+            size: (height, width),
+            obstacles: vec![false; height * width],
+            ..Default::default()
+        };
+        for (line_index, line) in input.lines().enumerate() {
+            for (char_index, char) in line.chars().enumerate() {
+                if char == '#' {
+                    map.obstacles[line_index * width + char_index] = true;
+                } else if let Some(direction) = Direction::from_glyph(char) {
+                    map.guards.push(((line_index, char_index), direction));
+                }
+            }
+        }
+        map
+    }
+
+    pub fn cells(&self) -> usize {
+        self.size.0 * self.size.1
+    }
+
+    pub fn index(&self, (y, x): (usize, usize)) -> usize {
+        y * self.size.1 + x
+    }
+
+    pub fn is_obstacle(&self, coord: (usize, usize)) -> bool {
+        self.obstacles[self.index(coord)]
+    }
+
+    /// The cell in front of a position, or `None` if it's off the map.
+    pub fn ahead(&self, (y, x): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        match direction {
+            Direction::Up => (y > 0).then(|| (y - 1, x)),
+            Direction::Right => (x + 1 < self.size.1).then_some((y, x + 1)),
+            Direction::Down => (y + 1 < self.size.0).then_some((y + 1, x)),
+            Direction::Left => (x > 0).then(|| (y, x - 1)),
+        }
+    }
+
+    /// The starting position and direction of the map's first guard, which is
+    /// the only guard in the puzzle.
+    pub fn guard(&self) -> ((usize, usize), Direction) {
+        *self.guards.first().expect("expected a guard on the map")
     }
 
-    /// Moves the guard one step, or rotates them.
+    /// A walk of the first guard, by themselves.
+    pub fn walk(&self) -> Walk {
+        let (guard, guard_direction) = self.guard();
+        Walk {
+            guard: Some(guard),
+            guard_direction,
+            visited: BitSet::new(self.cells()),
+            visited_direction: BitSet::new(4 * self.cells()),
+            trace: None,
+        }
+    }
+
+    /// Moves the guard one step, or rotates them. The extra obstacle is treated
+    /// as if it were on the map.
     /// Returns true if in a loop, false otherwise.
-    pub fn move_guard(&mut self) -> bool {
+    pub fn move_guard(&self, walk: &mut Walk, extra_obstacle: Option<(usize, usize)>) -> bool {
         // grid traversal, or object collision detection?
         // I'll do grid traversal, because that's more intuitive
-        if let Some((y, x)) = self.guard {
-            if self
+        if let Some(guard) = walk.guard {
+            let index = self.index(guard);
+            if !walk
                 .visited_direction
-                .contains(&(self.guard_direction, y, x))
+                .insert(walk.guard_direction.index() * self.cells() + index)
             {
                 return true;
             }
-            let (next_y, next_x) = match self.guard_direction {
-                Direction::Up => (y as isize - 1, x as isize),
-                Direction::Right => (y as isize, x as isize + 1),
-                Direction::Down => (y as isize + 1, x as isize),
-                Direction::Left => (y as isize, x as isize - 1),
-            };
-
-            self.visited.insert((y, x));
-            self.visited_direction.insert((self.guard_direction, y, x));
+            walk.visited.insert(index);
 
             // check out of bounds move
-            if next_y < 0
-                || next_y >= self.size.0 as isize
-                || next_x < 0
-                || next_x >= self.size.1 as isize
-            {
-                self.guard = None;
+            let Some(next) = self.ahead(guard, walk.guard_direction) else {
+                if let Some(trace) = &mut walk.trace {
+                    trace.push(Step {
+                        position: guard,
+                        direction: walk.guard_direction,
+                        turned: false,
+                    });
+                }
+                walk.guard = None;
                 return false;
-            }
+            };
 
-            if self.obstacles.contains(&(next_y as usize, next_x as usize)) {
-                self.guard_direction = self.guard_direction.rotate_right();
+            let turned = self.is_obstacle(next) || extra_obstacle == Some(next);
+            if let Some(trace) = &mut walk.trace {
+                trace.push(Step {
+                    position: guard,
+                    direction: walk.guard_direction,
+                    turned,
+                });
+            }
+            if turned {
+                walk.guard_direction = walk.guard_direction.rotate_right();
             } else {
-                self.guard = Some((next_y as usize, next_x as usize));
+                walk.guard = Some(next);
             }
         }
         false
     }
 
-    pub fn run(&mut self) -> MapFinishCondition {
+    pub fn run(
+        &self,
+        walk: &mut Walk,
+        extra_obstacle: Option<(usize, usize)>,
+    ) -> MapFinishCondition {
         loop {
-            if self.guard == None {
+            if walk.guard.is_none() {
                 return MapFinishCondition::Exit;
             }
-            if self.move_guard() {
+            if self.move_guard(walk, extra_obstacle) {
                 return MapFinishCondition::Loop;
             }
         }
@@ -124,33 +236,61 @@ impl Map {
 }
 
 fn part_1() {
-    let mut map = Map::new_from_file();
-    while map.guard.is_some() {
-        map.move_guard();
-    }
-    println!("{}", map.visited.len());
+    let map = Map::new_from_file();
+    let report = map.patrol();
+    println!("{}", report.coverage[0]);
 }
 
-fn part_2() {
-    // poorly optimized solution
-    let clonable = Map::new_from_file();
-    let mut map = clonable.clone();
-    let guard_start_position = map.guard.unwrap();
-    map.run();
-
-    let mut looping_obstacles = 0;
+/// Counts the cells where a new obstacle would trap the guard in a loop,
+/// trying the candidates across a number of threads.
+fn looping_obstacles(map: &Map, threads: usize) -> usize {
+    let jumps = JumpTable::new(map);
 
-    for visited in map.visited {
-        if visited == guard_start_position {
-            continue;
+    // the guard first runs into an obstacle on their path from the state just
+    // before it, so each simulation can skip everything up to that point
+    let mut candidates = vec![];
+    let mut tried = BitSet::new(map.cells());
+    tried.insert(map.index(map.guard().0));
+    let mut walk = map.walk();
+    while let Some(guard) = walk.guard {
+        if let Some(ahead) = map.ahead(guard, walk.guard_direction) {
+            if !map.is_obstacle(ahead) && tried.insert(map.index(ahead)) {
+                candidates.push((guard, walk.guard_direction, ahead));
+            }
         }
-        let mut edited_map = clonable.clone();
-        edited_map.obstacles.insert(visited);
-        if let MapFinishCondition::Loop = edited_map.run() {
-            looping_obstacles += 1;
+        // a guard that already loops has been everywhere they'll go
+        if map.move_guard(&mut walk, None) {
+            break;
         }
     }
 
+    let chunk_size = candidates.len().div_ceil(threads.max(1)).max(1);
+    thread::scope(|scope| {
+        let handles = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let jumps = &jumps;
+                scope.spawn(move || {
+                    let mut turns = BitSet::new(4 * map.cells());
+                    chunk
+                        .iter()
+                        .filter(|&&(guard, direction, obstacle)| {
+                            jumps.loops(&mut turns, guard, direction, obstacle)
+                        })
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("expected the thread to finish"))
+            .sum()
+    })
+}
+
+fn part_2() {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let looping_obstacles = looping_obstacles(&Map::new_from_file(), threads);
     println!("{}", looping_obstacles);
 }
 
@@ -158,3 +298,89 @@ pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints the guard's route, and saves it as a PNG and an animation, along
+/// with an animation of the guard stuck in a loop by the first new obstacle on
+/// the route that makes one.
+pub fn debug() {
+    let map = Map::new_from_file();
+    let mut walk = map.walk().with_trace();
+    map.run(&mut walk, None);
+    print!("{}", render::to_terminal(&map, &walk, None));
+    render::debug(&map, &walk, None, "guard.png");
+    render::animate(&map, &walk, None, 50, "guard.gif");
+
+    let route = walk.trace.unwrap_or_default();
+    let looping = route
+        .iter()
+        .map(|step| step.position)
+        .filter(|position| *position != map.guard().0)
+        .find_map(|obstacle| {
+            let mut walk = map.walk().with_trace();
+            let finish = map.run(&mut walk, Some(obstacle));
+            matches!(finish, MapFinishCondition::Loop).then_some((obstacle, walk))
+        });
+    if let Some((obstacle, walk)) = looping {
+        render::animate(&map, &walk, Some(obstacle), 50, "loop.gif");
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "....#.....\n.........#\n..........\n..#.......\n.......#..\n\
+                       ..........\n.#..^.....\n........#.\n#.........\n......#...";
+
+#[test]
+fn test_looping_obstacles() {
+    let map = Map::parse(EXAMPLE);
+    assert_eq!(looping_obstacles(&map, 1), 6);
+    assert_eq!(looping_obstacles(&map, 3), 6);
+
+    // the jump table agrees with walking step by step
+    let mut loops = 0;
+    for y in 0..map.size.0 {
+        for x in 0..map.size.1 {
+            if map.is_obstacle((y, x)) || (y, x) == map.guard().0 {
+                continue;
+            }
+            let mut walk = map.walk();
+            if let MapFinishCondition::Loop = map.run(&mut walk, Some((y, x))) {
+                loops += 1;
+            }
+        }
+    }
+    assert_eq!(loops, 6);
+}
+
+#[test]
+fn test_already_looping() {
+    // boxed in, so the guard only ever turns on the spot
+    let map = Map::parse(".#.\n#^#\n.#.");
+    assert_eq!(looping_obstacles(&map, 1), 0);
+
+    // walking round in a rectangle, where an obstacle on the route either
+    // keeps them looping or lets them out
+    let map = Map::parse(".#...\n.^..#\n.....\n#....\n...#.");
+    let mut walk = map.walk().with_trace();
+    assert!(matches!(map.run(&mut walk, None), MapFinishCondition::Loop));
+    let mut route = walk
+        .trace
+        .unwrap_or_default()
+        .iter()
+        .map(|step| step.position)
+        .filter(|position| *position != map.guard().0)
+        .collect::<Vec<_>>();
+    route.sort();
+    route.dedup();
+    let loops = route
+        .into_iter()
+        .filter(|obstacle| {
+            let mut walk = map.walk();
+            matches!(
+                map.run(&mut walk, Some(*obstacle)),
+                MapFinishCondition::Loop
+            )
+        })
+        .count();
+    assert_eq!(looping_obstacles(&map, 1), loops);
+    assert_eq!(looping_obstacles(&map, 3), loops);
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use super::{BitSet, Direction, Map};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatrolOutcome {
+    /// Every guard walked off the map.
+    Exited,
+    /// The guards ended up repeating the same moves together forever, every `period` ticks.
+    Cycle { period: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatrolReport {
+    /// How many distinct cells each guard visited, in the order the guards are on the map.
+    pub coverage: Vec<usize>,
+    pub outcome: PatrolOutcome,
+    /// How many ticks it took to find the outcome.
+    pub ticks: usize,
+}
+
+/// What a guard is about to do, before collisions between guards are settled.
+enum Intent {
+    Leave,
+    Turn,
+    Step((usize, usize)),
+}
+
+// the position and direction of every guard, or None for guards that have left
+type Guards = Vec<Option<((usize, usize), Direction)>>;
+
+impl Map {
+    /// Moves every guard on the map at once, a step or a turn each per tick,
+    /// until they have all left the map or are stuck in a cycle.
+    ///
+    /// Guards treat each other as obstacles: a guard turns instead of stepping
+    /// into a cell that another guard is standing on at the start of the tick.
+    /// When more than one guard steps towards the same empty cell, none of them
+    /// get it, and they all turn instead.
+    pub fn patrol(&self) -> PatrolReport {
+        let mut guards: Guards = self.guards.iter().copied().map(Some).collect();
+        let mut coverage = guards
+            .iter()
+            .flatten()
+            .map(|&(position, _)| {
+                let mut visited = BitSet::new(self.cells());
+                visited.insert(self.index(position));
+                visited
+            })
+            .collect::<Vec<_>>();
+
+        // Brent's cycle detection: the system is deterministic, so it's in a
+        // cycle as soon as any state repeats
+        let mut saved = guards.clone();
+        let mut power = 1;
+        let mut period = 0;
+        let mut ticks = 0;
+        let outcome = loop {
+            if guards.iter().all(Option::is_none) {
+                break PatrolOutcome::Exited;
+            }
+            self.tick(&mut guards);
+            ticks += 1;
+            for (guard, visited) in guards.iter().zip(&mut coverage) {
+                if let Some((position, _)) = guard {
+                    visited.insert(self.index(*position));
+                }
+            }
+
+            period += 1;
+            if guards == saved {
+                break PatrolOutcome::Cycle { period };
+            }
+            if period == power {
+                saved = guards.clone();
+                power *= 2;
+                period = 0;
+            }
+        };
+
+        PatrolReport {
+            coverage: coverage.iter().map(BitSet::len).collect(),
+            outcome,
+            ticks,
+        }
+    }
+
+    fn tick(&self, guards: &mut Guards) {
+        let occupied = guards
+            .iter()
+            .flatten()
+            .map(|(position, _)| *position)
+            .collect::<Vec<_>>();
+
+        let intents = guards
+            .iter()
+            .map(|guard| {
+                let (position, direction) = (*guard)?;
+                Some(match self.ahead(position, direction) {
+                    None => Intent::Leave,
+                    Some(next) if self.is_obstacle(next) || occupied.contains(&next) => {
+                        Intent::Turn
+                    }
+                    Some(next) => Intent::Step(next),
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut contested: HashMap<(usize, usize), usize> = HashMap::new();
+        for intent in intents.iter().flatten() {
+            if let Intent::Step(next) = intent {
+                *contested.entry(*next).or_default() += 1;
+            }
+        }
+
+        for (guard, intent) in guards.iter_mut().zip(intents) {
+            let (Some((position, direction)), Some(intent)) = (guard.as_mut(), intent) else {
+                continue;
+            };
+            match intent {
+                Intent::Step(next) if contested[&next] == 1 => *position = next,
+                Intent::Step(_) | Intent::Turn => *direction = direction.rotate_right(),
+                Intent::Leave => *guard = None,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_patrol_collisions() {
+    // the guards meet in the middle of the row and turn away from each other
+    let map = Map::parse("....\n>..<\n....");
+    let report = map.patrol();
+    assert_eq!(report.outcome, PatrolOutcome::Exited);
+    assert_eq!(report.coverage, vec![3, 3]);
+
+    // both guards want the middle cell, so neither get it, and they turn to
+    // face off the map and up the last column
+    let map = Map::parse("...\n>.<");
+    let report = map.patrol();
+    assert_eq!(report.coverage, vec![1, 2]);
+}
+
+#[test]
+fn test_patrol_cycle() {
+    // the example, with an obstacle that traps the first guard, and a second
+    // guard who walks straight off the map
+    let map = Map::parse(
+        "....#.....\n.........#\n..........\n..#.......\n.......#..\n\
+         ..........\n.#.#^.....\n........#.\n#.........\nv.....#...",
+    );
+    let report = map.patrol();
+    assert_eq!(report.outcome, PatrolOutcome::Cycle { period: 22 });
+    assert_eq!(report.coverage, vec![18, 1]);
+}
@@ -0,0 +1,154 @@
+use std::fs::File;
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, ImageBuffer, Rgba, RgbaImage,
+};
+
+use super::{Direction, Map, Step, Walk};
+
+const CELL_SIZE: u32 = 4;
+const BACKGROUND: Rgba<u8> = Rgba([20, 20, 30, 255]);
+const OBSTACLE: Rgba<u8> = Rgba([120, 120, 120, 255]);
+const EXTRA_OBSTACLE: Rgba<u8> = Rgba([230, 60, 60, 255]);
+const PATH: Rgba<u8> = Rgba([60, 110, 220, 255]);
+const LOOP: Rgba<u8> = Rgba([240, 200, 50, 255]);
+const GUARD: Rgba<u8> = Rgba([80, 220, 100, 255]);
+
+fn is_vertical(direction: Direction) -> bool {
+    matches!(direction, Direction::Up | Direction::Down)
+}
+
+fn glyph(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '^',
+        Direction::Right => '>',
+        Direction::Down => 'v',
+        Direction::Left => '<',
+    }
+}
+
+/// Draws the map with the guard's route, using `|` and `-` for where they walked
+/// up and down or across, and `+` where they turned or crossed their own path.
+/// The extra obstacle is drawn as `O`.
+pub fn to_terminal(map: &Map, walk: &Walk, extra_obstacle: Option<(usize, usize)>) -> String {
+    // (walked vertically, walked horizontally) for every cell
+    let mut walked = vec![(false, false); map.cells()];
+    for step in walk.trace.iter().flatten() {
+        let cell = &mut walked[map.index(step.position)];
+        if step.turned {
+            *cell = (true, true);
+        } else if is_vertical(step.direction) {
+            cell.0 = true;
+        } else {
+            cell.1 = true;
+        }
+    }
+
+    let mut output = String::new();
+    for y in 0..map.size.0 {
+        for x in 0..map.size.1 {
+            output.push(if map.is_obstacle((y, x)) {
+                '#'
+            } else if extra_obstacle == Some((y, x)) {
+                'O'
+            } else if let Some((_, direction)) =
+                map.guards.iter().find(|(guard, _)| *guard == (y, x))
+            {
+                glyph(*direction)
+            } else {
+                match walked[map.index((y, x))] {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    (false, false) => '.',
+                }
+            });
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Draws the map after the first `steps` of the walk, with any of the steps
+/// that are part of the walk's loop highlighted.
+fn draw(
+    map: &Map,
+    trace: &[Step],
+    steps: usize,
+    loop_start: Option<usize>,
+    extra_obstacle: Option<(usize, usize)>,
+) -> RgbaImage {
+    let mut colors = vec![BACKGROUND; map.cells()];
+    for (index, obstacle) in map.obstacles.iter().enumerate() {
+        if *obstacle {
+            colors[index] = OBSTACLE;
+        }
+    }
+    if let Some(extra_obstacle) = extra_obstacle {
+        colors[map.index(extra_obstacle)] = EXTRA_OBSTACLE;
+    }
+    for (index, step) in trace[..steps].iter().enumerate() {
+        let in_loop = loop_start.is_some_and(|loop_start| index >= loop_start);
+        colors[map.index(step.position)] = if in_loop { LOOP } else { PATH };
+    }
+    if let Some(step) = trace[..steps].last() {
+        colors[map.index(step.position)] = GUARD;
+    }
+
+    let width = map.size.1 as u32;
+    ImageBuffer::from_fn(width * CELL_SIZE, map.size.0 as u32 * CELL_SIZE, |x, y| {
+        colors[(y / CELL_SIZE * width + x / CELL_SIZE) as usize]
+    })
+}
+
+/// Saves the map at the end of the walk as a PNG.
+pub fn debug(map: &Map, walk: &Walk, extra_obstacle: Option<(usize, usize)>, image_name: &str) {
+    let trace = walk.trace.as_deref().unwrap_or_default();
+    let image = draw(map, trace, trace.len(), walk.loop_start(), extra_obstacle);
+    image.save(image_name).unwrap();
+}
+
+/// Saves the walk as an animated GIF, drawing a frame every `steps_per_frame` steps.
+pub fn animate(
+    map: &Map,
+    walk: &Walk,
+    extra_obstacle: Option<(usize, usize)>,
+    steps_per_frame: usize,
+    image_name: &str,
+) {
+    let trace = walk.trace.as_deref().unwrap_or_default();
+    let loop_start = walk.loop_start();
+    let frames = (0..=trace.len())
+        .step_by(steps_per_frame.max(1))
+        .chain([trace.len()])
+        .map(|steps| {
+            let image = draw(map, trace, steps, loop_start, extra_obstacle);
+            Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(40, 1))
+        });
+
+    let mut encoder = GifEncoder::new(File::create(image_name).unwrap());
+    encoder.set_repeat(Repeat::Infinite).unwrap();
+    encoder.encode_frames(frames).unwrap();
+}
+
+#[test]
+fn test_to_terminal() {
+    let map = Map::parse(super::EXAMPLE);
+    let mut walk = map.walk().with_trace();
+    map.run(&mut walk, Some((6, 3)));
+    assert_eq!(walk.loop_start(), Some(0));
+    assert_eq!(
+        to_terminal(&map, &walk, Some((6, 3))),
+        "....#.....\n\
+         ....+---+#\n\
+         ....|...|.\n\
+         ..#.|...|.\n\
+         ....|..#|.\n\
+         ....|...|.\n\
+         .#.O^---+.\n\
+         ........#.\n\
+         #.........\n\
+         ......#...\n"
+    );
+}
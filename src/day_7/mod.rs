@@ -1,33 +1,18 @@
-use std::u64;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
-enum Operator {
-    Add,
-    Mul,
-    Concat,
-}
+use number::Number;
+use operator::Operator;
+use search::Solver;
 
-impl Operator {
-    pub fn operate(self, a: u64, b: u64) -> u64 {
-        match self {
-            Operator::Add => a.saturating_add(b),
-            Operator::Mul => a.saturating_mul(b),
-            Operator::Concat => {
-                let digit_count = b.checked_ilog10().unwrap_or(0) + 1;
-                a.saturating_mul(10_u64.pow(digit_count)).saturating_add(b)
-            }
-        }
-    }
-}
+mod number;
+mod operator;
+mod search;
 
-#[test]
-fn test_operation() {
-    assert_eq!(Operator::Concat.operate(12, 345), 12345);
-}
-
-fn get_input() -> Vec<(u64, Vec<u64>)> {
-    let input = include_str!("input.txt");
-    // let input = "7290: 6 8 6 15";
+/// Parses one equation per line, as `N`s.
+fn parse<N: Number>(input: &str) -> Vec<(N, Vec<N>)>
+where
+    N::Err: fmt::Debug,
+{
     input
         .lines()
         .map(|line| {
@@ -35,83 +20,42 @@ fn get_input() -> Vec<(u64, Vec<u64>)> {
             let first = splits.next().expect("expected string before :");
             let after = splits.next().expect("expected string after :");
             (
-                u64::from_str_radix(first, 10).expect("number to convert"),
+                first.parse().expect("number to convert"),
                 after
                     .split_whitespace()
-                    .map(|word| u64::from_str_radix(word, 10).expect("number to convert"))
+                    .map(|word| word.parse().expect("number to convert"))
                     .collect(),
             )
         })
         .collect()
 }
 
-fn dfs(target: u64, acc: u64, remaining: &[u64]) -> Option<Vec<Operator>> {
-    if acc > target {
-        return None;
-    }
-    if remaining.is_empty() {
-        if acc == target {
-            return Some(vec![]);
-        }
-        return None;
-    }
-    for op in [Operator::Add, Operator::Mul] {
-        let result = op.operate(acc, remaining[0]);
-        if let Some(mut op_list) = dfs(target, result, &remaining[1..]) {
-            op_list.push(op);
-            return Some(op_list);
-        }
-    }
-
-    None
-}
-
-fn dfs_2(target: u64, acc: u64, remaining: &[u64]) -> Option<Vec<Operator>> {
-    if acc > target {
-        return None;
-    }
-    if remaining.is_empty() {
-        if acc == target {
-            return Some(vec![]);
-        }
-        return None;
-    }
-    for op in [Operator::Add, Operator::Mul, Operator::Concat] {
-        let result = op.operate(acc, remaining[0]);
-        if let Some(mut op_list) = dfs_2(target, result, &remaining[1..]) {
-            op_list.push(op);
-            return Some(op_list);
-        }
-    }
-
-    None
+/// The equations as u128s, so targets past 64 bits can still be solved.
+fn get_input() -> Vec<(u128, Vec<u128>)> {
+    parse(include_str!("input.txt"))
 }
 
 fn part_1() {
     let input = get_input();
-    let sum: u64 = input
+    let sum: u128 = input
         .iter()
-        .filter_map(|(target, operands)| dfs(*target, operands[0], &operands[1..]).map(|_| *target))
+        .filter_map(|(target, operands)| {
+            Solver::new(&[Operator::Add, Operator::Mul])
+                .solve(*target, operands)
+                .map(|_| *target)
+        })
         .sum();
     println!("{sum}");
 }
 
 fn part_2() {
     let input = get_input();
-    let sum: u64 = input
+    let sum: u128 = input
         .iter()
         .filter_map(|(target, operands)| {
-            dfs_2(*target, operands[0], &operands[1..]).map(|_| {
-                // println!(
-                //     "{}",
-                //     ops.iter()
-                //         .rev()
-                //         .map(|op| format!("{:#?}", op))
-                //         .collect::<Vec<String>>()
-                //         .join(",")
-                // );
-                *target
-            })
+            Solver::new(&[Operator::Add, Operator::Mul, Operator::Concat(10)])
+                .solve(*target, operands)
+                .map(|_| *target)
         })
         .sum();
     println!("{sum}");
@@ -121,3 +65,24 @@ pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints every way of solving each equation with concatenation, then how many
+/// equations could be solved and in how many ways altogether.
+pub fn debug() {
+    let solver = Solver::new(&[Operator::Add, Operator::Mul, Operator::Concat(10)]);
+    let equations = get_input();
+    let mut solved = 0;
+    let mut ways = 0;
+    for (target, operands) in &equations {
+        for expression in solver.solutions(*target, operands) {
+            println!("{target} = {expression}");
+        }
+        let count = solver.count(*target, operands);
+        solved += (count > 0) as usize;
+        ways += count;
+    }
+    println!(
+        "{solved} of {} equations solved, in {ways} ways",
+        equations.len()
+    );
+}
@@ -0,0 +1,73 @@
+use std::{
+    fmt,
+    ops::{BitXor, Div, Rem},
+    str::FromStr,
+};
+
+/// The unsigned integers equations can be solved over. The arithmetic is all
+/// checked, so an overflow is never mistaken for a result.
+pub trait Number:
+    Copy
+    + Ord
+    + fmt::Debug
+    + fmt::Display
+    + FromStr
+    + From<u64>
+    + BitXor<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+    fn checked_pow(self, exponent: u32) -> Option<Self>;
+    fn checked_ilog(self, base: Self) -> Option<u32>;
+    fn is_multiple_of(self, other: Self) -> bool;
+}
+
+macro_rules! impl_number {
+    ($($number:ty),*) => {
+        $(
+            impl Number for $number {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$number>::MAX;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$number>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$number>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$number>::checked_mul(self, other)
+                }
+
+                fn checked_div(self, other: Self) -> Option<Self> {
+                    <$number>::checked_div(self, other)
+                }
+
+                fn checked_pow(self, exponent: u32) -> Option<Self> {
+                    <$number>::checked_pow(self, exponent)
+                }
+
+                fn checked_ilog(self, base: Self) -> Option<u32> {
+                    <$number>::checked_ilog(self, base)
+                }
+
+                fn is_multiple_of(self, other: Self) -> bool {
+                    <$number>::is_multiple_of(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(u64, u128);
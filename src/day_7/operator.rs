@@ -0,0 +1,158 @@
+use std::{fmt, ops::RangeInclusive};
+
+use super::number::Number;
+
+/// A binary operator that can go between two operands of an equation. Every
+/// operator is undefined where it would overflow.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Operator<N: Number = u64> {
+    Add,
+    /// Only defined when the result isn't negative.
+    Sub,
+    Mul,
+    /// Integer division, rounding down. Dividing by zero isn't defined.
+    Div,
+    Xor,
+    /// Writes the digits of `b` after the digits of `a`, in the given base.
+    Concat(u64),
+    /// Any other operator, which returns `None` where it isn't defined. The
+    /// search can't undo it, so it has to try every value on its left.
+    Custom {
+        symbol: &'static str,
+        apply: fn(N, N) -> Option<N>,
+    },
+}
+
+/// The values `a` could have had, if `a op b` gave some result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Undone<N> {
+    Values(RangeInclusive<N>),
+    /// Too many values to list, or the operator can't say.
+    Any,
+}
+
+/// `base` to the power of the number of digits `b` has in that base, which is
+/// what concatenating `b` multiplies the left side by.
+fn concat_shift<N: Number>(base: u64, b: N) -> Option<N> {
+    let base = N::from(base);
+    let digit_count = b.checked_ilog(base).unwrap_or(0) + 1;
+    base.checked_pow(digit_count)
+}
+
+impl<N: Number> Operator<N> {
+    /// `a op b`, or `None` if it isn't defined.
+    pub fn operate(self, a: N, b: N) -> Option<N> {
+        match self {
+            Operator::Add => a.checked_add(b),
+            Operator::Sub => a.checked_sub(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Div => a.checked_div(b),
+            Operator::Xor => Some(a ^ b),
+            Operator::Concat(base) => {
+                if base < 2 {
+                    return None;
+                }
+                match concat_shift(base, b) {
+                    Some(shift) => a.checked_mul(shift)?.checked_add(b),
+                    // only zero can have that many digits shifted onto it
+                    None => (a == N::ZERO).then_some(b),
+                }
+            }
+            Operator::Custom { apply, .. } => apply(a, b),
+        }
+    }
+
+    /// Every `a` for which `a op b == result`, or `None` if there aren't any.
+    pub fn undo(self, result: N, b: N) -> Option<Undone<N>> {
+        let single = |a: Option<N>| a.map(|a| Undone::Values(a..=a));
+        match self {
+            Operator::Add => single(result.checked_sub(b)),
+            Operator::Sub => single(result.checked_add(b)),
+            Operator::Mul if b == N::ZERO => (result == N::ZERO).then_some(Undone::Any),
+            Operator::Mul => single(result.is_multiple_of(b).then(|| result / b)),
+            Operator::Div if b == N::ZERO => None,
+            Operator::Div => {
+                let low = result.checked_mul(b)?;
+                let high = low.checked_add(b.checked_sub(N::ONE)?).unwrap_or(N::MAX);
+                Some(Undone::Values(low..=high))
+            }
+            Operator::Xor => single(Some(result ^ b)),
+            Operator::Concat(base) => {
+                if base < 2 {
+                    return None;
+                }
+                match concat_shift(base, b) {
+                    Some(shift) => single((result % shift == b).then(|| result / shift)),
+                    // only zero can have that many digits shifted onto it
+                    None => single((result == b).then_some(N::ZERO)),
+                }
+            }
+            Operator::Custom { .. } => Some(Undone::Any),
+        }
+    }
+
+    /// Whether `a op b` can be smaller than `a`, in which case a search can't
+    /// give up once its running total passes the target.
+    pub fn can_shrink(self, b: N) -> bool {
+        match self {
+            Operator::Add | Operator::Concat(_) => false,
+            Operator::Sub | Operator::Xor => b != N::ZERO,
+            Operator::Mul => b == N::ZERO,
+            Operator::Div => b != N::ONE,
+            Operator::Custom { .. } => true,
+        }
+    }
+}
+
+impl<N: Number> fmt::Display for Operator<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::Xor => write!(f, "^"),
+            Operator::Concat(10) => write!(f, "||"),
+            Operator::Concat(base) => write!(f, "||{base}"),
+            Operator::Custom { symbol, .. } => write!(f, "{symbol}"),
+        }
+    }
+}
+
+#[test]
+fn test_operation() {
+    let concat: Operator = Operator::Concat(10);
+    assert_eq!(concat.operate(12, 345), Some(12345));
+    assert_eq!(concat.undo(12345, 345), Some(Undone::Values(12..=12)));
+    assert_eq!(concat.undo(12345, 45), Some(Undone::Values(123..=123)));
+    assert_eq!(concat.undo(12345, 44), None);
+    let binary: Operator = Operator::Concat(2);
+    assert_eq!(binary.operate(0b10, 0b11), Some(0b1011));
+
+    let [add, sub, mul, div, xor]: [Operator; 5] = [
+        Operator::Add,
+        Operator::Sub,
+        Operator::Mul,
+        Operator::Div,
+        Operator::Xor,
+    ];
+    assert_eq!(mul.undo(12, 5), None);
+    assert_eq!(mul.undo(0, 0), Some(Undone::Any));
+    assert_eq!(add.undo(4, 5), None);
+    assert_eq!(sub.operate(4, 5), None);
+    assert_eq!(div.undo(3, 4), Some(Undone::Values(12..=15)));
+    assert_eq!(xor.undo(0b110, 0b011), Some(Undone::Values(0b101..=0b101)));
+
+    // overflowing is undefined rather than saturating
+    let max = u64::MAX;
+    assert_eq!(add.operate(max, 1), None);
+    assert_eq!(mul.operate(max, 2), None);
+    assert_eq!(concat.operate(max / 10, 6), None);
+    assert_eq!(concat.operate(0, max), Some(max));
+    assert_eq!(div.undo(max / 2, 2), Some(Undone::Values(max - 1..=max)));
+    assert_eq!(
+        Operator::<u128>::Mul.operate(max.into(), 2),
+        Some(2 * u128::from(max))
+    );
+}
@@ -0,0 +1,366 @@
+use std::{fmt, ops::ControlFlow};
+
+use super::{
+    number::Number,
+    operator::{Operator, Undone},
+};
+
+/// Which end of an equation the search starts from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Folds the operands from the left, giving up when the total passes the
+    /// target if no operator can bring it back down.
+    #[allow(dead_code)]
+    Forward,
+    /// Undoes the operands from the right, starting at the target, which rules
+    /// out most operators at every step. Operators that can't be undone, or
+    /// that could have had too many values on their left, are searched
+    /// forwards instead.
+    #[default]
+    Backward,
+}
+
+/// Operands with operators between them, evaluated from left to right.
+#[derive(Debug, Clone)]
+pub struct Expression<N: Number = u64> {
+    pub operands: Vec<N>,
+    pub operators: Vec<Operator<N>>,
+}
+
+impl<N: Number> Expression<N> {
+    #[allow(dead_code)]
+    pub fn evaluate(&self) -> Option<N> {
+        let (first, rest) = self.operands.split_first()?;
+        rest.iter()
+            .zip(&self.operators)
+            .try_fold(*first, |acc, (operand, op)| op.operate(acc, *operand))
+    }
+}
+
+impl<N: Number> fmt::Display for Expression<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((first, rest)) = self.operands.split_first() else {
+            return Ok(());
+        };
+        write!(f, "{first}")?;
+        for (operand, op) in rest.iter().zip(&self.operators) {
+            write!(f, " {op} {operand}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the operators, out of a given set, that make equations true.
+#[derive(Debug, Clone)]
+pub struct Solver<'a, N: Number = u64> {
+    operators: &'a [Operator<N>],
+    strategy: Strategy,
+}
+
+impl<'a, N: Number> Solver<'a, N> {
+    pub fn new(operators: &'a [Operator<N>]) -> Self {
+        Self {
+            operators,
+            strategy: Strategy::default(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// The first expression found which evaluates to `target`.
+    pub fn solve(&self, target: N, operands: &[N]) -> Option<Expression<N>> {
+        let mut found = None;
+        self.search(target, operands, &mut |operators| {
+            found = Some(operators.to_vec());
+            ControlFlow::Break(())
+        });
+        found.map(|operators| Expression {
+            operands: operands.to_vec(),
+            operators,
+        })
+    }
+
+    /// Every expression which evaluates to `target`.
+    pub fn solutions(&self, target: N, operands: &[N]) -> Vec<Expression<N>> {
+        let mut found = vec![];
+        self.search(target, operands, &mut |operators| {
+            found.push(Expression {
+                operands: operands.to_vec(),
+                operators: operators.to_vec(),
+            });
+            ControlFlow::Continue(())
+        });
+        found
+    }
+
+    /// How many expressions evaluate to `target`, without keeping them.
+    pub fn count(&self, target: N, operands: &[N]) -> usize {
+        let mut count = 0;
+        self.search(target, operands, &mut |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        count
+    }
+
+    fn search(
+        &self,
+        target: N,
+        operands: &[N],
+        on_found: &mut dyn FnMut(&[Operator<N>]) -> ControlFlow<()>,
+    ) {
+        let Some(first) = operands.first() else {
+            return;
+        };
+        let mut search = Search {
+            operators: self.operators,
+            operands,
+            chosen: vec![Operator::Add; operands.len() - 1],
+            // the running total can only come back down to the target if
+            // something can make it smaller
+            bounded: !operands[1..]
+                .iter()
+                .any(|&b| self.operators.iter().any(|op| op.can_shrink(b))),
+            on_found,
+        };
+        let _ = match self.strategy {
+            Strategy::Forward => search.forward(*first, 1, Goal::Equals(target)),
+            Strategy::Backward => search.backward(target, operands.len()),
+        };
+    }
+}
+
+/// What the running total of a forward search has to do once it runs out of
+/// operands.
+#[derive(Debug, Clone, Copy)]
+enum Goal<N: Number> {
+    Equals(N),
+    /// The first `len` operands, then `op operand`, have to equal `target`.
+    /// This is where the backward search hands over to the forward search.
+    EqualsAfter {
+        len: usize,
+        op: Operator<N>,
+        operand: N,
+        target: N,
+    },
+}
+
+/// The most values the backward search tries one at a time for an operator
+/// that can be undone more than one way, before searching forwards instead.
+const MAX_BACKWARD_VALUES: u64 = 16;
+
+struct Search<'a, 'f, N: Number> {
+    operators: &'a [Operator<N>],
+    operands: &'a [N],
+    // the operator before each operand after the first
+    chosen: Vec<Operator<N>>,
+    bounded: bool,
+    on_found: &'f mut dyn FnMut(&[Operator<N>]) -> ControlFlow<()>,
+}
+
+impl<N: Number> Search<'_, '_, N> {
+    /// Tries every operator before `operands[index]`, given the running total
+    /// of the operands before it.
+    fn forward(&mut self, acc: N, index: usize, goal: Goal<N>) -> ControlFlow<()> {
+        let (len, reached) = match goal {
+            Goal::Equals(target) => {
+                if self.bounded && acc > target {
+                    return ControlFlow::Continue(());
+                }
+                (self.operands.len(), acc == target)
+            }
+            Goal::EqualsAfter {
+                len,
+                op,
+                operand,
+                target,
+            } => (
+                len,
+                index == len && op.operate(acc, operand) == Some(target),
+            ),
+        };
+        if index == len {
+            if reached {
+                return (self.on_found)(&self.chosen);
+            }
+            return ControlFlow::Continue(());
+        }
+        for &op in self.operators {
+            if let Some(result) = op.operate(acc, self.operands[index]) {
+                self.chosen[index - 1] = op;
+                self.forward(result, index + 1, goal)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Tries every operator before the last of the first `len` operands, which
+    /// have to evaluate to `target`.
+    fn backward(&mut self, target: N, len: usize) -> ControlFlow<()> {
+        if len == 1 {
+            if self.operands[0] == target {
+                return (self.on_found)(&self.chosen);
+            }
+            return ControlFlow::Continue(());
+        }
+        let operand = self.operands[len - 1];
+        for &op in self.operators {
+            let Some(undone) = op.undo(target, operand) else {
+                continue;
+            };
+            self.chosen[len - 2] = op;
+            match undone {
+                // only the first operand is left, so it's either in range or not
+                Undone::Values(values) if len == 2 => {
+                    if values.contains(&self.operands[0]) {
+                        (self.on_found)(&self.chosen)?;
+                    }
+                }
+                Undone::Values(values)
+                    if values.end().checked_sub(*values.start())
+                        <= Some(N::from(MAX_BACKWARD_VALUES)) =>
+                {
+                    let (mut previous, last) = values.into_inner();
+                    loop {
+                        self.chosen[len - 2] = op;
+                        self.backward(previous, len - 1)?;
+                        if previous >= last {
+                            break;
+                        }
+                        previous = previous.checked_add(N::ONE).unwrap_or(last);
+                    }
+                }
+                // too many values to try each one, like undoing a division by a
+                // large number, so the operands before are searched forwards
+                Undone::Values(_) | Undone::Any => {
+                    let goal = Goal::EqualsAfter {
+                        len: len - 1,
+                        op,
+                        operand,
+                        target,
+                    };
+                    self.forward(self.operands[0], 1, goal)?;
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "190: 10 19\n3267: 81 40 27\n83: 17 5\n156: 15 6\n7290: 6 8 6 15\n\
+                       161011: 16 10 13\n192: 17 8 14\n21037: 9 7 18 13\n292: 11 6 16 20";
+
+#[test]
+fn test_strategies() {
+    let equations = super::parse(EXAMPLE);
+    for (operators, expected) in [
+        (&[Operator::Add, Operator::Mul][..], 3749),
+        (
+            &[Operator::Add, Operator::Mul, Operator::Concat(10)][..],
+            11387,
+        ),
+    ] {
+        for strategy in [Strategy::Forward, Strategy::Backward] {
+            let solver = Solver::new(operators).with_strategy(strategy);
+            let sum: u64 = equations
+                .iter()
+                .filter(|(target, operands)| solver.solve(*target, operands).is_some())
+                .map(|(target, _)| target)
+                .sum();
+            assert_eq!(sum, expected, "{strategy:?}");
+        }
+    }
+
+    let solver: Solver = Solver::new(&[Operator::Add, Operator::Mul]);
+    let found = solver.solve(3267, &[81, 40, 27]).unwrap();
+    assert_eq!(found.to_string(), "81 * 40 + 27");
+    let found = solver.solve(0, &[3, 4, 0]).unwrap();
+    assert_eq!(found.to_string(), "3 + 4 * 0");
+}
+
+#[test]
+fn test_solutions() {
+    let operators: [Operator; 7] = [
+        Operator::Add,
+        Operator::Sub,
+        Operator::Mul,
+        Operator::Div,
+        Operator::Xor,
+        Operator::Concat(2),
+        Operator::Custom {
+            symbol: "max",
+            apply: |a, b| Some(a.max(b)),
+        },
+    ];
+    for (target, operands) in [
+        (3267, &[81, 40, 27][..]),
+        (0, &[3, 4, 0]),
+        (6, &[7, 2, 3, 1]),
+    ] {
+        let mut found = vec![];
+        for strategy in [Strategy::Forward, Strategy::Backward] {
+            let solver = Solver::new(&operators).with_strategy(strategy);
+            let mut solutions = solver
+                .solutions(target, operands)
+                .iter()
+                .inspect(|solution| assert_eq!(solution.evaluate(), Some(target)))
+                .map(|solution| solution.to_string())
+                .collect::<Vec<_>>();
+            solutions.sort();
+            assert_eq!(solver.count(target, operands), solutions.len());
+            found.push(solutions);
+        }
+        assert_eq!(found[0], found[1], "{target}");
+    }
+
+    let solver: Solver = Solver::new(&[Operator::Add, Operator::Sub, Operator::Div]);
+    let mut solutions = solver
+        .solutions(1, &[7, 2, 3])
+        .iter()
+        .map(|solution| solution.to_string())
+        .collect::<Vec<_>>();
+    solutions.sort();
+    assert_eq!(solutions, vec!["7 - 2 / 3", "7 / 2 / 3"]);
+}
+
+#[test]
+fn test_overflow() {
+    let max = u64::MAX;
+    for strategy in [Strategy::Forward, Strategy::Backward] {
+        // saturating would make this `u64::MAX`
+        let solver: Solver = Solver::new(&[Operator::Add, Operator::Mul]).with_strategy(strategy);
+        assert!(solver.solve(max, &[max, 2]).is_none());
+        assert!(solver.solve(max, &[max - 1, 1]).is_some());
+
+        // concatenating makes too many digits for 64 bits
+        let operators = [Operator::Add, Operator::Mul, Operator::Concat(10)];
+        let solver = Solver::<u128>::new(&operators).with_strategy(strategy);
+        let found = solver.solve((u128::from(max) * 10 + 2) * 21, &[max.into(), 2, 21]);
+        assert_eq!(found.unwrap().to_string(), format!("{max} || 2 * 21"));
+    }
+
+    // targets past 64 bits parse as u128s
+    let equations = super::parse::<u128>("36893488147419103230: 18446744073709551615 2");
+    let solver = Solver::new(&[Operator::Add, Operator::Mul]);
+    let found = solver.solve(equations[0].0, &equations[0].1);
+    assert_eq!(found.unwrap().to_string(), format!("{max} * 2"));
+}
+
+#[test]
+fn test_wide_undo() {
+    // undoing `/ 1_000_000_000_000` gives a trillion values, too many to try
+    let operators = [Operator::Add, Operator::Div];
+    for strategy in [Strategy::Forward, Strategy::Backward] {
+        let solver: Solver = Solver::new(&operators).with_strategy(strategy);
+        assert!(solver.solve(1, &[7, 1_000_000_000_000]).is_none());
+        assert!(solver.solve(2, &[7, 1_000_000_000_000, 3]).is_none());
+        let found = solver.solve(3, &[7, 1_000_000_000_000, 3]).unwrap();
+        assert_eq!(found.to_string(), "7 / 1000000000000 + 3");
+        assert_eq!(solver.count(0, &[7, 1_000_000_000_000, 3, 2]), 1);
+    }
+}
@@ -2,15 +2,44 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
+mod render;
+mod report;
+
 #[derive(Debug, Clone)]
 struct Map {
     size: (usize, usize),
     nodes: HashMap<char, Vec<(usize, usize)>>,
 }
 
+/// Where antinodes are, in line with two antennas of the same frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resonance {
+    /// Where one of the antennas is `far / near` times as far away as the
+    /// other, beyond either antenna, and also between them if `between` is set.
+    Ratio {
+        far: usize,
+        near: usize,
+        between: bool,
+    },
+    /// Anywhere in line.
+    Harmonic,
+}
+
+/// The greatest common divisor, which is `b` if `a` is zero.
+fn gcd(a: usize, b: usize) -> usize {
+    if a == 0 {
+        b
+    } else {
+        gcd(b % a, a)
+    }
+}
+
 impl Map {
     pub fn new_from_file() -> Self {
-        let input = include_str!("input.txt");
+        Self::parse(include_str!("input.txt"))
+    }
+
+    pub fn parse(input: &str) -> Self {
         let height = input.lines().count();
         let width = input.lines().nth(0).unwrap().chars().count();
         let nodes = input
@@ -40,58 +69,110 @@ impl Map {
             && coord.1 < self.size.1 as isize
     }
 
-    fn consec(a: usize, b: usize) -> isize {
-        (2 * b as isize) - (a as isize)
+    /// Every pair of antennas out of some with the same frequency.
+    fn pairs(
+        locs: &[(usize, usize)],
+    ) -> impl Iterator<Item = ((isize, isize), (isize, isize))> + '_ {
+        locs.iter()
+            .tuple_combinations()
+            .map(|(a, b)| ((a.0 as isize, a.1 as isize), (b.0 as isize, b.1 as isize)))
     }
 
-    fn collinear(a: (usize, usize), b: (usize, usize), c: (usize, usize)) -> bool {
-        let vec_1 = ((b.0 as isize - a.0 as isize), (b.1 as isize - a.1 as isize));
-        let vec_2 = ((c.0 as isize - a.0 as isize), (c.1 as isize - a.1 as isize));
-        // check when magnitude of cross-product is 0
-        vec_1.0 * vec_2.1 - vec_1.1 * vec_2.0 == 0
+    /// The point `numerator / denominator` of the way from `a` to `b`, if
+    /// it's on the grid.
+    fn between(
+        a: (isize, isize),
+        b: (isize, isize),
+        numerator: isize,
+        denominator: isize,
+    ) -> Option<(isize, isize)> {
+        let scaled = ((b.0 - a.0) * numerator, (b.1 - a.1) * numerator);
+        (denominator != 0 && scaled.0 % denominator == 0 && scaled.1 % denominator == 0)
+            .then(|| (a.0 + scaled.0 / denominator, a.1 + scaled.1 / denominator))
     }
 
-    fn aligned(&self, coords: (usize, usize)) -> bool {
-        self.nodes.iter().any(|(_, locs)| {
-            locs.iter()
-                .tuple_combinations()
-                .any(|(loc_a, loc_b)| Self::collinear(*loc_a, *loc_b, coords))
-        })
+    /// The antinodes of two antennas, which may be off the map.
+    fn pair_antinodes(
+        &self,
+        a: (isize, isize),
+        b: (isize, isize),
+        resonance: Resonance,
+    ) -> Vec<(isize, isize)> {
+        match resonance {
+            Resonance::Ratio { far, near, between } => {
+                let (far, near) = (far as isize, near as isize);
+                // a point `t` of the way from `a` to `b` is `|t| / |t - 1|`
+                // times as far from `a` as from `b`
+                [(a, b), (b, a)]
+                    .into_iter()
+                    .flat_map(|(a, b)| {
+                        [
+                            Self::between(a, b, far, far - near),
+                            Self::between(a, b, far, far + near).filter(|_| between),
+                        ]
+                    })
+                    .flatten()
+                    .collect()
+            }
+            Resonance::Harmonic => {
+                let (dy, dx) = (b.0 - a.0, b.1 - a.1);
+                let g = gcd(dy.unsigned_abs(), dx.unsigned_abs()) as isize;
+                let step = (dy / g, dx / g);
+                [step, (-step.0, -step.1)]
+                    .into_iter()
+                    .flat_map(|step| {
+                        (0..)
+                            .map(move |i| (a.0 + step.0 * i, a.1 + step.1 * i))
+                            .take_while(|coords| self.in_bounds(*coords))
+                    })
+                    .collect()
+            }
+        }
     }
 
-    pub fn antinodes_1(&self) -> Vec<(usize, usize)> {
-        self.nodes
-            .iter()
-            .flat_map(|(_, locs)| {
-                locs.iter().tuple_combinations().flat_map(|(a, b)| {
-                    [
-                        (Self::consec(a.0, b.0), Self::consec(a.1, b.1)),
-                        (Self::consec(b.0, a.0), Self::consec(b.1, a.1)),
-                    ]
-                    .into_iter()
-                    .filter(|coords| self.in_bounds(*coords))
-                    .map(|(y, x)| (y as usize, x as usize))
-                })
-            })
+    fn antinodes_of<'a>(
+        &'a self,
+        locs: impl Iterator<Item = &'a Vec<(usize, usize)>> + 'a,
+        resonance: Resonance,
+    ) -> Vec<(usize, usize)> {
+        locs.flat_map(|locs| Self::pairs(locs))
+            .flat_map(|(a, b)| self.pair_antinodes(a, b, resonance))
+            .filter(|coords| self.in_bounds(*coords))
+            .map(|(y, x)| (y as usize, x as usize))
             .unique()
             .collect()
     }
 
-    pub fn antinodes_2(&self) -> Vec<(usize, usize)> {
-        (0..self.size.0)
-            .cartesian_product(0..self.size.1)
-            .filter(|coords| self.aligned(*coords))
-            .collect()
+    /// Every antinode of the antennas with the same frequency as each other.
+    pub fn antinodes(&self, resonance: Resonance) -> Vec<(usize, usize)> {
+        self.antinodes_of(self.nodes.values(), resonance)
+    }
+
+    /// The antinodes of the antennas with one frequency.
+    pub fn frequency_antinodes(
+        &self,
+        frequency: char,
+        resonance: Resonance,
+    ) -> Vec<(usize, usize)> {
+        self.antinodes_of(self.nodes.get(&frequency).into_iter(), resonance)
     }
 }
 
 fn part_1() {
-    let count = Map::new_from_file().antinodes_1().len();
+    let map = Map::new_from_file();
+    let count = map
+        .antinodes(Resonance::Ratio {
+            far: 2,
+            near: 1,
+            between: false,
+        })
+        .len();
     println!("{count}");
 }
 
 fn part_2() {
-    let count = Map::new_from_file().antinodes_2().len();
+    let map = Map::new_from_file();
+    let count = map.antinodes(Resonance::Harmonic).len();
     println!("{count}");
 }
 
@@ -99,3 +180,45 @@ pub fn compute() {
     part_1();
     part_2();
 }
+
+/// Prints a report and the map of the antinodes for both parts, and saves the
+/// harmonic antinodes as a PNG.
+pub fn debug() {
+    let map = Map::new_from_file();
+    let resonance = Resonance::Ratio {
+        far: 2,
+        near: 1,
+        between: false,
+    };
+    println!("{}", report::report(&map, resonance));
+    print!("{}", render::to_terminal(&map, &map.antinodes(resonance)));
+
+    println!();
+    println!("{}", report::report(&map, Resonance::Harmonic));
+    let antinodes = map.antinodes(Resonance::Harmonic);
+    print!("{}", render::to_terminal(&map, &antinodes));
+    render::debug(&map, &antinodes, "antinodes.png");
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "............\n........0...\n.....0......\n.......0....\n\
+                       ....0.......\n......A.....\n............\n............\n\
+                       ........A...\n.........A..\n............\n............";
+
+#[test]
+fn test_antinodes() {
+    let ratio = |far, near, between| Resonance::Ratio { far, near, between };
+    let map = Map::parse(EXAMPLE);
+    assert_eq!(map.antinodes(ratio(2, 1, false)).len(), 14);
+    assert_eq!(map.antinodes(Resonance::Harmonic).len(), 34);
+
+    // a third of the way from one antenna to the other is twice as far from it
+    let map = Map::parse("a.....\n......\n......\n...a..");
+    assert_eq!(map.antinodes(ratio(2, 1, false)), vec![]);
+    let mut found = map.antinodes(ratio(2, 1, true));
+    found.sort();
+    assert_eq!(found, vec![(1, 1), (2, 2)]);
+    assert_eq!(map.antinodes(ratio(1, 1, true)), vec![]);
+    assert_eq!(map.antinodes(ratio(3, 2, true)), vec![]);
+    assert_eq!(map.antinodes(Resonance::Harmonic).len(), 4);
+}
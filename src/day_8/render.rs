@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use image::{ImageBuffer, Rgb};
+use itertools::Itertools;
+
+use crate::render::PALETTE;
+
+use super::Map;
+
+const BACKGROUND: [u8; 3] = [20, 20, 30];
+const ANTINODE: [u8; 3] = [230, 60, 60];
+const CELL_SIZE: u32 = 8;
+// how much of an antinode shows around an antenna on top of it
+const BORDER: u32 = 2;
+
+/// Draws the antennas, and `#` for antinodes. Antennas on top of antinodes
+/// keep their frequency, but are highlighted in red.
+pub fn to_terminal(map: &Map, antinodes: &[(usize, usize)]) -> String {
+    let antinodes = antinodes.iter().collect::<HashSet<_>>();
+    let mut grid = vec![vec!['.'; map.size.1]; map.size.0];
+    for (frequency, locs) in &map.nodes {
+        for &(y, x) in locs {
+            grid[y][x] = *frequency;
+        }
+    }
+
+    let mut output = String::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, chr) in row.iter().enumerate() {
+            match (antinodes.contains(&(y, x)), chr) {
+                (true, '.') => output.push('#'),
+                (true, _) => output += &format!("\x1b[1;41m{chr}\x1b[0m"),
+                (false, _) => output.push(*chr),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Saves the map as a PNG, with a colour for each frequency's antennas, and
+/// red for antinodes.
+pub fn debug(map: &Map, antinodes: &[(usize, usize)], image_name: &str) {
+    // the palette's red is left for antinodes
+    let palette = &PALETTE[1..];
+    let mut colors = vec![vec![None; map.size.1]; map.size.0];
+    for (index, frequency) in map.nodes.keys().sorted().enumerate() {
+        for &(y, x) in &map.nodes[frequency] {
+            colors[y][x] = Some(palette[index % palette.len()]);
+        }
+    }
+    let antinodes = antinodes.iter().collect::<HashSet<_>>();
+
+    let image = ImageBuffer::from_fn(
+        map.size.1 as u32 * CELL_SIZE,
+        map.size.0 as u32 * CELL_SIZE,
+        |x, y| {
+            let cell = ((y / CELL_SIZE) as usize, (x / CELL_SIZE) as usize);
+            let (inner_x, inner_y) = (x % CELL_SIZE, y % CELL_SIZE);
+            let on_border =
+                inner_x.min(inner_y) < BORDER || inner_x.max(inner_y) >= CELL_SIZE - BORDER;
+            let antinode = antinodes.contains(&cell);
+            Rgb(match colors[cell.0][cell.1] {
+                Some(color) if !(antinode && on_border) => color,
+                _ if antinode => ANTINODE,
+                _ => BACKGROUND,
+            })
+        },
+    );
+    image.save(image_name).unwrap();
+}
+
+#[test]
+fn test_to_terminal() {
+    use super::Resonance;
+
+    let map = Map::parse(super::EXAMPLE);
+    let antinodes = map.antinodes(Resonance::Ratio {
+        far: 2,
+        near: 1,
+        between: false,
+    });
+    let rendered = to_terminal(&map, &antinodes);
+    let expected = "......#....#\n...#....0...\n....#0....#.\n..#....0....\n\
+                    ....0....#..\n.#....A.....\n...#........\n#......#....\n\
+                    ........A...\n.........A..\n..........#.\n..........#.\n";
+    // the antinode on top of an antenna is highlighted rather than drawn
+    assert_eq!(rendered.matches("\x1b[1;41mA\x1b[0m").count(), 1);
+    assert_eq!(rendered.replace("\x1b[1;41mA\x1b[0m", "A"), expected);
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, fmt};
+
+use itertools::Itertools;
+
+use super::{Map, Resonance};
+
+/// The antinodes of the antennas with one frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrequencyReport {
+    pub frequency: char,
+    pub antinodes: usize,
+    /// How many of them are also antinodes of another frequency.
+    pub shared: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Ordered by frequency.
+    pub frequencies: Vec<FrequencyReport>,
+    /// How many distinct antinodes there are between all the frequencies.
+    pub total: usize,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in &self.frequencies {
+            writeln!(
+                f,
+                "{}: {} antinodes, {} shared with other frequencies",
+                report.frequency, report.antinodes, report.shared
+            )?;
+        }
+        write!(f, "{} antinodes in total", self.total)
+    }
+}
+
+pub fn report(map: &Map, resonance: Resonance) -> Report {
+    let antinodes = map
+        .nodes
+        .keys()
+        .sorted()
+        .map(|&frequency| (frequency, map.frequency_antinodes(frequency, resonance)))
+        .collect::<Vec<_>>();
+
+    // how many frequencies each antinode belongs to
+    let mut frequency_counts: HashMap<(usize, usize), usize> = HashMap::new();
+    for antinode in antinodes.iter().flat_map(|(_, antinodes)| antinodes) {
+        *frequency_counts.entry(*antinode).or_default() += 1;
+    }
+
+    Report {
+        frequencies: antinodes
+            .iter()
+            .map(|(frequency, antinodes)| FrequencyReport {
+                frequency: *frequency,
+                antinodes: antinodes.len(),
+                shared: antinodes
+                    .iter()
+                    .filter(|antinode| frequency_counts[antinode] > 1)
+                    .count(),
+            })
+            .collect(),
+        total: frequency_counts.len(),
+    }
+}
+
+#[test]
+fn test_report() {
+    let map = Map::parse(super::EXAMPLE);
+    let report = report(
+        &map,
+        Resonance::Ratio {
+            far: 2,
+            near: 1,
+            between: false,
+        },
+    );
+    assert_eq!(
+        report.frequencies,
+        vec![
+            FrequencyReport {
+                frequency: '0',
+                antinodes: 10,
+                shared: 1,
+            },
+            FrequencyReport {
+                frequency: 'A',
+                antinodes: 5,
+                shared: 1,
+            },
+        ]
+    );
+    assert_eq!(report.total, 14);
+    assert_eq!(
+        report.to_string().lines().next(),
+        Some("0: 10 antinodes, 1 shared with other frequencies")
+    );
+}
@@ -0,0 +1,382 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    ops::Range,
+};
+
+/// A run of blocks on the disk, all belonging to one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extent {
+    pub file_id: usize,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Extent {
+    /// The sum of each block's position times the file's ID.
+    pub fn checksum(&self) -> usize {
+        // the sum of the positions from `start` to `start + len - 1`
+        self.file_id * (2 * self.start + self.len.saturating_sub(1)) * self.len / 2
+    }
+}
+
+pub fn checksum(extents: &[Extent]) -> usize {
+    extents.iter().map(Extent::checksum).sum()
+}
+
+/// Which free span a whole file moves into, out of the ones to the left of it
+/// that it fits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    /// The leftmost.
+    First,
+    /// The smallest, then the leftmost of those.
+    Best,
+    /// The largest, then the leftmost of those.
+    Worst,
+}
+
+/// How files are moved into free space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// One block at a time, from the end of the disk into the leftmost free
+    /// block, which splits files up.
+    Fragment,
+    /// Whole files, from the highest ID down, each tried once.
+    WholeFile(Fit),
+    /// Whole files, from the highest ID down, over and over until none of them
+    /// can move any further left.
+    UntilStable(Fit),
+}
+
+/// Fills the free spans from the left with the blocks at the end of the disk,
+/// a run at a time rather than block by block.
+pub fn fragment(mut files: Vec<Extent>, free: &[(usize, usize)]) -> Vec<Extent> {
+    let mut extents = vec![];
+    let mut spans = free.iter().copied();
+    let mut span = spans.next();
+    while let Some(mut file) = files.pop() {
+        while let Some((start, len)) = span.filter(|(start, _)| *start < file.start && file.len > 0)
+        {
+            let moved = len.min(file.len);
+            extents.push(Extent {
+                file_id: file.file_id,
+                start,
+                len: moved,
+            });
+            // the moved blocks come off the end of the file
+            file.len -= moved;
+            span = if moved < len {
+                Some((start + moved, len - moved))
+            } else {
+                spans.next()
+            };
+        }
+        if file.len > 0 {
+            extents.push(file);
+        }
+    }
+
+    extents.sort_by_key(|extent| extent.start);
+    // the last span a file moves into can end where the rest of it starts
+    extents.dedup_by(|next, extent| {
+        let joined = next.file_id == extent.file_id && extent.start + extent.len == next.start;
+        if joined {
+            extent.len += next.len;
+        }
+        joined
+    });
+    extents
+}
+
+/// The free spans between extents, which have to be ordered by position.
+pub fn gaps(extents: &[Extent]) -> Vec<(usize, usize)> {
+    extents
+        .iter()
+        .filter(|extent| extent.len > 0)
+        .scan(0, |end, extent| {
+            let gap = (*end, extent.start - *end);
+            *end = extent.start + extent.len;
+            Some(gap)
+        })
+        .filter(|(_, len)| *len > 0)
+        .collect()
+}
+
+/// Free spans, ordered by position, as the leaves of a segment tree of the
+/// largest length under each node. The leftmost span at least some length
+/// long, or the largest span, out of the ones before a position, is found in
+/// O(log n) however many different lengths there are. Spans only ever shrink
+/// from the left, so they stay in order.
+struct FreeSpans {
+    starts: Vec<usize>,
+    largest: Vec<usize>,
+    // where the leaves start in `largest`
+    leaves: usize,
+}
+
+impl FreeSpans {
+    fn new(free: &[(usize, usize)]) -> Self {
+        let leaves = free.len().next_power_of_two();
+        let mut largest = vec![0; 2 * leaves];
+        for (index, (_, len)) in free.iter().enumerate() {
+            largest[leaves + index] = *len;
+        }
+        for node in (1..leaves).rev() {
+            largest[node] = largest[2 * node].max(largest[2 * node + 1]);
+        }
+        Self {
+            starts: free.iter().map(|(start, _)| *start).collect(),
+            largest,
+            leaves,
+        }
+    }
+
+    /// How many spans start before `position`.
+    fn count_before(&self, position: usize) -> usize {
+        self.starts.partition_point(|start| *start < position)
+    }
+
+    /// The length of the largest of the first `count` spans.
+    fn largest(&self, count: usize) -> usize {
+        let (mut low, mut high) = (self.leaves, self.leaves + count);
+        let mut largest = 0;
+        while low < high {
+            if low % 2 == 1 {
+                largest = largest.max(self.largest[low]);
+                low += 1;
+            }
+            if high % 2 == 1 {
+                high -= 1;
+                largest = largest.max(self.largest[high]);
+            }
+            low /= 2;
+            high /= 2;
+        }
+        largest
+    }
+
+    /// The leftmost of the first `count` spans that's at least `len` long.
+    fn leftmost(&self, len: usize, count: usize) -> Option<usize> {
+        self.leftmost_under(1, 0..self.leaves, len, count)
+    }
+
+    fn leftmost_under(
+        &self,
+        node: usize,
+        spans: Range<usize>,
+        len: usize,
+        count: usize,
+    ) -> Option<usize> {
+        if spans.start >= count || self.largest[node] < len {
+            return None;
+        }
+        if spans.len() == 1 {
+            return Some(spans.start);
+        }
+        let middle = spans.start + spans.len() / 2;
+        self.leftmost_under(2 * node, spans.start..middle, len, count)
+            .or_else(|| self.leftmost_under(2 * node + 1, middle..spans.end, len, count))
+    }
+
+    /// Takes `len` blocks from the start of a span, returning where they were.
+    fn take(&mut self, index: usize, len: usize) -> usize {
+        let start = self.starts[index];
+        self.starts[index] += len;
+        let mut node = self.leaves + index;
+        self.largest[node] -= len;
+        while node > 1 {
+            node /= 2;
+            self.largest[node] = self.largest[2 * node].max(self.largest[2 * node + 1]);
+        }
+        start
+    }
+}
+
+/// Moves whole files, from the highest ID down, into a free span they fit in,
+/// if there's one to the left of where they are. Returns whether any moved,
+/// with the files ordered by position.
+pub fn whole_files(files: &mut [Extent], free: &[(usize, usize)], fit: Fit) -> bool {
+    files.sort_by_key(|file| Reverse(file.file_id));
+    // the space a file leaves behind isn't used until the next pass, so
+    // there's no need to track it
+    let moved = match fit {
+        Fit::First | Fit::Worst => {
+            let mut spans = FreeSpans::new(free);
+            let mut moved = false;
+            for file in files.iter_mut().filter(|file| file.len > 0) {
+                let count = spans.count_before(file.start);
+                let found = match fit {
+                    Fit::Worst => Some(spans.largest(count))
+                        .filter(|largest| *largest >= file.len)
+                        .and_then(|largest| spans.leftmost(largest, count)),
+                    _ => spans.leftmost(file.len, count),
+                };
+                if let Some(index) = found {
+                    file.start = spans.take(index, file.len);
+                    moved = true;
+                }
+            }
+            moved
+        }
+        Fit::Best => best_fit(files, free),
+    };
+    files.sort_by_key(|file| file.start);
+    moved
+}
+
+/// `whole_files` with the smallest span a file fits in, which the segment tree
+/// can't find. Free spans are kept in a min-heap of start positions per size
+/// instead, so the leftmost span of each size is at the top of a heap, but
+/// finding the smallest one left of a file means looking through every size
+/// from the file's up, which for the puzzle's single digit sizes is at most
+/// nine heaps.
+fn best_fit(files: &mut [Extent], free: &[(usize, usize)]) -> bool {
+    let mut spans: BTreeMap<usize, BinaryHeap<Reverse<usize>>> = BTreeMap::new();
+    for &(start, len) in free {
+        spans.entry(len).or_default().push(Reverse(start));
+    }
+
+    let mut moved = false;
+    for file in files.iter_mut().filter(|file| file.len > 0) {
+        let Some((span_len, start)) = spans
+            .range(file.len..)
+            .filter_map(|(span_len, starts)| Some((*span_len, starts.peek()?.0)))
+            .find(|(_, start)| *start < file.start)
+        else {
+            continue;
+        };
+        let starts = spans
+            .get_mut(&span_len)
+            .expect("expected a span of that size");
+        starts.pop();
+        if starts.is_empty() {
+            spans.remove(&span_len);
+        }
+        if span_len > file.len {
+            spans
+                .entry(span_len - file.len)
+                .or_default()
+                .push(Reverse(start + file.len));
+        }
+        file.start = start;
+        moved = true;
+    }
+    moved
+}
+
+/// Repeats `whole_files` with the space left behind by each pass until the
+/// files stop moving.
+pub fn until_stable(files: &mut [Extent], fit: Fit) -> usize {
+    let mut passes = 1;
+    files.sort_by_key(|file| file.start);
+    while whole_files(files, &gaps(files), fit) {
+        passes += 1;
+    }
+    passes
+}
+
+#[test]
+fn test_whole_files() {
+    use super::disk::DiskMap;
+
+    let disk_map = DiskMap::parse("2333133121414131402").unwrap();
+    let extents = disk_map.compact(Strategy::WholeFile(Fit::First));
+    // 00992111777.44.333....5555.6666.....8888..
+    assert_eq!(
+        extents
+            .iter()
+            .map(|extent| (extent.file_id, extent.start))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, 0),
+            (9, 2),
+            (2, 4),
+            (1, 5),
+            (7, 8),
+            (4, 12),
+            (3, 15),
+            (5, 22),
+            (6, 27),
+            (8, 36)
+        ]
+    );
+}
+
+#[test]
+fn test_fits() {
+    use super::disk::DiskMap;
+
+    let layout = |disk_map: &str, strategy| {
+        DiskMap::parse(disk_map)
+            .unwrap()
+            .compact(strategy)
+            .iter()
+            .map(|extent| (extent.file_id, extent.start))
+            .collect::<Vec<_>>()
+    };
+
+    // 0....111..22.3 becomes 0322.111 with first fit, and 0111....22..3 with
+    // best fit
+    assert_eq!(
+        layout("1,4,3,2,2,1,1", Strategy::WholeFile(Fit::First)),
+        vec![(0, 0), (3, 1), (2, 2), (1, 5)]
+    );
+    assert_eq!(
+        layout("1,4,3,2,2,1,1", Strategy::WholeFile(Fit::Best)),
+        vec![(0, 0), (1, 1), (2, 8), (3, 12)]
+    );
+
+    // 0.1...2 becomes 021 with first fit, and 01.2 with worst fit, which is
+    // another pass away from 012
+    assert_eq!(
+        layout("1,1,1,3,1", Strategy::WholeFile(Fit::First)),
+        vec![(0, 0), (2, 1), (1, 2)]
+    );
+    assert_eq!(
+        layout("1,1,1,3,1", Strategy::WholeFile(Fit::Worst)),
+        vec![(0, 0), (1, 1), (2, 3)]
+    );
+    assert_eq!(
+        layout("1,1,1,3,1", Strategy::UntilStable(Fit::Worst)),
+        vec![(0, 0), (1, 1), (2, 2)]
+    );
+}
+
+#[test]
+fn test_many_sizes() {
+    use super::disk::DiskMap;
+
+    // checks against trying every span, on sizes up to 100 from a simple LCG
+    let mut seed = 12345u64;
+    let sizes = (0..3000)
+        .map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ((seed >> 33) % 100).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let disk_map = DiskMap::parse(&sizes).unwrap();
+    for fit in [Fit::First, Fit::Best, Fit::Worst] {
+        let mut files = disk_map.files();
+        let mut spans = disk_map.free_spans();
+        files.sort_by_key(|file| Reverse(file.file_id));
+        for file in files.iter_mut().filter(|file| file.len > 0) {
+            let candidates = spans
+                .iter_mut()
+                .filter(|(start, len)| *start < file.start && *len >= file.len);
+            let found = match fit {
+                Fit::First => candidates.min_by_key(|(start, _)| *start),
+                Fit::Best => candidates.min_by_key(|(start, len)| (*len, *start)),
+                Fit::Worst => candidates.min_by_key(|(start, len)| (Reverse(*len), *start)),
+            };
+            if let Some((start, len)) = found {
+                file.start = *start;
+                *start += file.len;
+                *len -= file.len;
+            }
+        }
+        files.sort_by_key(|file| file.start);
+        assert_eq!(disk_map.compact(Strategy::WholeFile(fit)), files, "{fit:?}");
+    }
+}
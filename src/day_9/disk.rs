@@ -0,0 +1,217 @@
+use std::fmt;
+
+use crate::parse;
+
+use super::{
+    compact::{self, Extent, Strategy},
+    metrics::{Metrics, Run},
+};
+
+/// Alternating file and free space sizes, starting with a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskMap {
+    sizes: Vec<usize>,
+}
+
+/// Why a disk map couldn't be parsed. Entries are counted from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidSize { entry: usize, text: String },
+    Empty,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidSize { entry, text } => {
+                write!(f, "entry {entry}: expected a size, found {text:?}")
+            }
+            ParseError::Empty => write!(f, "expected at least one size"),
+        }
+    }
+}
+
+impl DiskMap {
+    /// Parses either a single digit per size, like the puzzle input, or sizes
+    /// of any length separated by commas.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let input = input.trim();
+        let entries: Vec<&str> = if input.contains(',') {
+            input.split(',').map(str::trim).collect()
+        } else {
+            input
+                .char_indices()
+                .filter(|(_, chr)| !chr.is_whitespace())
+                .map(|(index, chr)| &input[index..index + chr.len_utf8()])
+                .collect()
+        };
+        if entries.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let sizes = entries
+            .iter()
+            .enumerate()
+            .map(|(entry, text)| {
+                parse::digits(text).ok_or_else(|| ParseError::InvalidSize {
+                    entry,
+                    text: text.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { sizes })
+    }
+
+    /// Where each file starts, ordered by position, which is also by ID.
+    pub fn files(&self) -> Vec<Extent> {
+        self.extents()
+            .step_by(2)
+            .enumerate()
+            .map(|(file_id, (start, len))| Extent {
+                file_id,
+                start,
+                len,
+            })
+            .collect()
+    }
+
+    /// The start and length of each run of free space, ordered by position.
+    pub fn free_spans(&self) -> Vec<(usize, usize)> {
+        self.extents()
+            .skip(1)
+            .step_by(2)
+            .filter(|(_, len)| *len > 0)
+            .collect()
+    }
+
+    fn extents(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.sizes.iter().scan(0, |position, &len| {
+            let start = *position;
+            *position += len;
+            Some((start, len))
+        })
+    }
+
+    /// How many blocks long the disk is.
+    pub fn len(&self) -> usize {
+        self.sizes.iter().sum()
+    }
+
+    /// Moves the files into free space to the left of them, returning where
+    /// every part of every file ends up, ordered by position.
+    #[allow(dead_code)]
+    pub fn compact(&self, strategy: Strategy) -> Vec<Extent> {
+        self.run(strategy).extents
+    }
+
+    /// Compacts the disk, and measures how fragmented it is afterwards.
+    pub fn run(&self, strategy: Strategy) -> Run {
+        let mut passes = 1;
+        let extents = match strategy {
+            Strategy::Fragment => compact::fragment(self.files(), &self.free_spans()),
+            Strategy::WholeFile(fit) => {
+                let mut files = self.files();
+                compact::whole_files(&mut files, &self.free_spans(), fit);
+                files
+            }
+            Strategy::UntilStable(fit) => {
+                let mut files = self.files();
+                passes = compact::until_stable(&mut files, fit);
+                files
+            }
+        };
+        Run {
+            metrics: Metrics::measure(&extents, self.len()),
+            extents,
+            passes,
+        }
+    }
+}
+
+#[test]
+fn test_parse() {
+    let digits = DiskMap::parse("12345\n").unwrap();
+    assert_eq!(digits.sizes, vec![1, 2, 3, 4, 5]);
+    let list = DiskMap::parse("1, 20,300\n").unwrap();
+    assert_eq!(list.sizes, vec![1, 20, 300]);
+    assert_eq!(list.free_spans(), vec![(1, 20)],);
+
+    assert_eq!(
+        DiskMap::parse("1,+2"),
+        Err(ParseError::InvalidSize {
+            entry: 1,
+            text: "+2".to_string(),
+        })
+    );
+    assert_eq!(
+        DiskMap::parse("12a"),
+        Err(ParseError::InvalidSize {
+            entry: 2,
+            text: "a".to_string(),
+        })
+    );
+    assert_eq!(DiskMap::parse("\n"), Err(ParseError::Empty));
+}
+
+#[test]
+fn test_compact() {
+    use super::compact::Fit;
+
+    let disk_map = DiskMap::parse("2333133121414131402").unwrap();
+    assert_eq!(
+        compact::checksum(&disk_map.compact(Strategy::Fragment)),
+        1928
+    );
+    assert_eq!(
+        compact::checksum(&disk_map.compact(Strategy::WholeFile(Fit::First))),
+        2858
+    );
+
+    // the same disk map with every size a thousand times bigger
+    let large = disk_map
+        .sizes
+        .iter()
+        .map(|size| (size * 1000).to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let large = DiskMap::parse(&large).unwrap();
+    let extents = large.compact(Strategy::WholeFile(Fit::First));
+    assert_eq!(extents.len(), 10);
+    assert!(extents.iter().all(|extent| extent.len % 1000 == 0));
+    let fragmented = large.compact(Strategy::Fragment);
+    assert_eq!(
+        fragmented.iter().map(|extent| extent.len).sum::<usize>(),
+        28000
+    );
+    assert_eq!(compact::checksum(&fragmented), checksum_by_block(&large));
+}
+
+/// The checksum after fragmenting, by moving one block at a time.
+#[cfg(test)]
+fn checksum_by_block(disk_map: &DiskMap) -> usize {
+    let mut blocks = disk_map
+        .sizes
+        .iter()
+        .enumerate()
+        .flat_map(|(index, size)| std::iter::repeat_n((index % 2 == 0).then_some(index / 2), *size))
+        .collect::<Vec<_>>();
+    let mut left = 0;
+    let mut right = blocks.len();
+    loop {
+        while left < right && blocks[left].is_some() {
+            left += 1;
+        }
+        while left < right && blocks[right - 1].is_none() {
+            right -= 1;
+        }
+        if left + 1 >= right {
+            break;
+        }
+        blocks.swap(left, right - 1);
+    }
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(position, file_id)| position * file_id.unwrap_or(0))
+        .sum()
+}
@@ -0,0 +1,106 @@
+use std::{collections::HashMap, fmt};
+
+use super::compact::{self, Extent};
+
+/// How fragmented a disk is after compacting it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Files split into more than one extent.
+    pub fragmented_files: usize,
+    /// Including the free space after the last file.
+    pub largest_free_extent: usize,
+    /// Runs of free space with a file after them.
+    pub gaps: usize,
+    pub gap_blocks: usize,
+}
+
+impl Metrics {
+    /// Measures extents, ordered by position, on a disk `len` blocks long.
+    pub fn measure(extents: &[Extent], len: usize) -> Self {
+        let mut pieces: HashMap<usize, usize> = HashMap::new();
+        for extent in extents.iter().filter(|extent| extent.len > 0) {
+            *pieces.entry(extent.file_id).or_default() += 1;
+        }
+        let gaps = compact::gaps(extents);
+        let end = extents
+            .iter()
+            .map(|extent| extent.start + extent.len)
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            fragmented_files: pieces.values().filter(|pieces| **pieces > 1).count(),
+            largest_free_extent: gaps
+                .iter()
+                .map(|(_, len)| *len)
+                .chain([len.saturating_sub(end)])
+                .max()
+                .unwrap_or(0),
+            gaps: gaps.len(),
+            gap_blocks: gaps.iter().map(|(_, len)| len).sum(),
+        }
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} fragmented files, {} gaps of {} blocks, largest free extent of {} blocks",
+            self.fragmented_files, self.gaps, self.gap_blocks, self.largest_free_extent
+        )
+    }
+}
+
+/// The outcome of compacting a disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    /// Where every part of every file ended up, ordered by position.
+    pub extents: Vec<Extent>,
+    /// How many times the files were moved, until none of them could move.
+    pub passes: usize,
+    pub metrics: Metrics,
+}
+
+impl Run {
+    pub fn checksum(&self) -> usize {
+        compact::checksum(&self.extents)
+    }
+}
+
+#[test]
+fn test_metrics() {
+    use super::{
+        compact::{Fit, Strategy},
+        disk::DiskMap,
+    };
+
+    let disk_map = DiskMap::parse("2333133121414131402").unwrap();
+    // 0099811188827773336446555566..............
+    let run = disk_map.run(Strategy::Fragment);
+    assert_eq!(
+        run.metrics,
+        Metrics {
+            fragmented_files: 2,
+            largest_free_extent: 14,
+            gaps: 0,
+            gap_blocks: 0,
+        }
+    );
+    // 00992111777.44.333....5555.6666.....8888..
+    let run = disk_map.run(Strategy::WholeFile(Fit::First));
+    assert_eq!(
+        run.metrics,
+        Metrics {
+            fragmented_files: 0,
+            largest_free_extent: 5,
+            gaps: 5,
+            gap_blocks: 12,
+        }
+    );
+    assert_eq!(run.passes, 1);
+    assert_eq!(
+        run.metrics.to_string(),
+        "0 fragmented files, 5 gaps of 12 blocks, largest free extent of 5 blocks"
+    );
+}
@@ -33,6 +33,10 @@ mod day_23;
 mod day_24;
 mod day_25;
 
+mod bitset;
+mod parse;
+mod render;
+
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
@@ -44,6 +48,7 @@ enum Day {
     All,
     Last,
     Day { day: u8 },
+    Debug { day: u8 },
 }
 
 fn main() {
@@ -77,10 +82,29 @@ fn main() {
         day_25::compute,
     ];
 
+    let debugs: [(u8, fn()); 9] = [
+        (3, day_3::debug),
+        (4, day_4::debug),
+        (5, day_5::debug),
+        (6, day_6::debug),
+        (7, day_7::debug),
+        (8, day_8::debug),
+        (9, day_9::debug),
+        (10, day_10::debug),
+        (11, day_11::debug),
+    ];
+
     let day_arg = match cli.day.unwrap_or(Day::Last) {
         Day::All => None,
         Day::Last => Some(problems.len() as u8),
         Day::Day { day } => Some(day),
+        Day::Debug { day } => {
+            match debugs.iter().find(|(debug_day, _)| *debug_day == day) {
+                Some((_, debug)) => debug(),
+                None => eprintln!("day {day} has nothing to debug"),
+            }
+            return;
+        }
     };
 
     problems
@@ -0,0 +1,10 @@
+use std::str::FromStr;
+
+/// Parses an unsigned number made of nothing but ASCII digits. `str::parse` on
+/// its own would also take a leading `+`.
+pub fn digits<N: FromStr>(text: &str) -> Option<N> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
@@ -0,0 +1,10 @@
+/// Colours that are easy to tell apart on a dark background, for highlighting
+/// different things on a grid by their index.
+pub const PALETTE: [[u8; 3]; 6] = [
+    [230, 80, 80],
+    [80, 200, 90],
+    [90, 140, 240],
+    [235, 200, 60],
+    [200, 100, 220],
+    [60, 200, 210],
+];